@@ -0,0 +1,65 @@
+use std::{io, path::Path};
+
+/// Copy the NTFS alternate data streams of `source` (e.g. `Zone.Identifier`)
+/// onto `target`; plain copies keep only the main stream.
+#[cfg(windows)]
+pub fn copy_streams(source: &Path, target: &Path) -> io::Result<()> {
+    use std::{ffi::OsString, fs, path::PathBuf};
+
+    let with_stream = |path: &Path, name: &str| {
+        let mut full: OsString = path.as_os_str().to_owned();
+        full.push(":");
+        full.push(name);
+        PathBuf::from(full)
+    };
+    for name in stream_names(source)? {
+        fs::copy(with_stream(source, &name), with_stream(target, &name))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn copy_streams(_source: &Path, _target: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Names of the alternate streams of `path`, without the `:…:$DATA` decoration
+#[cfg(windows)]
+fn stream_names(path: &Path) -> io::Result<Vec<String>> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::{
+        Foundation::{ERROR_HANDLE_EOF, INVALID_HANDLE_VALUE},
+        Storage::FileSystem::{
+            FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard, WIN32_FIND_STREAM_DATA,
+        },
+    };
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    // SAFETY: WIN32_FIND_STREAM_DATA is plain data; the find handle is closed below
+    let mut data: WIN32_FIND_STREAM_DATA = unsafe { std::mem::zeroed() };
+    let handle = unsafe { FindFirstStreamW(wide.as_ptr(), FindStreamInfoStandard, (&raw mut data).cast(), 0) };
+    if handle == INVALID_HANDLE_VALUE {
+        let error = io::Error::last_os_error();
+        // Filesystems without streams report end-of-list straight away
+        return match error.raw_os_error() {
+            Some(code) if code == ERROR_HANDLE_EOF as i32 => Ok(Vec::new()),
+            _ => Err(error),
+        };
+    }
+
+    let mut names = Vec::new();
+    loop {
+        let length = data.cStreamName.iter().position(|&c| c == 0).unwrap_or(data.cStreamName.len());
+        let raw = String::from_utf16_lossy(&data.cStreamName[..length]);
+        // "::$DATA" is the main stream; alternate ones look like ":name:$DATA"
+        let name = raw.strip_prefix(':').and_then(|n| n.strip_suffix(":$DATA"));
+        if let Some(name) = name.filter(|n| !n.is_empty()) {
+            names.push(name.to_string());
+        }
+        if unsafe { FindNextStreamW(handle, (&raw mut data).cast()) } == 0 {
+            break;
+        }
+    }
+    unsafe { FindClose(handle) };
+    Ok(names)
+}
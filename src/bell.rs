@@ -0,0 +1,42 @@
+use console::Term;
+
+/// Alert the user that the run is over: a terminal bell, plus a system sound
+/// when built with the `sound` feature.
+pub fn ring(term: &Term, success: bool) {
+    let _ = term.write_str("\x07");
+    let _ = term.flush();
+
+    #[cfg(feature = "sound")]
+    play_sound(success);
+    #[cfg(not(feature = "sound"))]
+    let _ = success;
+}
+
+/// Best-effort system sound through the platform's stock player
+#[cfg(feature = "sound")]
+fn play_sound(success: bool) {
+    use std::process::{Command, Stdio};
+
+    let mut command = if cfg!(target_os = "macos") {
+        let sound = if success { "Glass" } else { "Basso" };
+        let mut c = Command::new("afplay");
+        c.arg(format!("/System/Library/Sounds/{}.aiff", sound));
+        c
+    } else if cfg!(windows) {
+        let sound = if success { "Asterisk" } else { "Hand" };
+        let mut c = Command::new("powershell");
+        c.args(["-NoProfile", "-Command", &format!("[System.Media.SystemSounds]::{}.Play()", sound)]);
+        c
+    } else {
+        let sound = if success { "complete" } else { "dialog-error" };
+        let mut c = Command::new("canberra-gtk-play");
+        c.args(["-i", sound]);
+        c
+    };
+
+    // Wait so the sound is not cut off when copro exits right after
+    let _ = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
@@ -0,0 +1,197 @@
+use crate::{format_rate, format_size, parse_size};
+use anyhow::{Context, Result};
+use console::style;
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Arguments of `copro bench`
+#[derive(clap::Args, Clone)]
+pub struct BenchArgs {
+    /// directory to benchmark (a scratch folder is created inside and removed afterwards)
+    target: PathBuf,
+
+    /// number of files in the synthetic workload
+    #[arg(long, default_value_t = 200)]
+    files: usize,
+
+    /// file sizes of the workload, used in turn (comma separated)
+    #[arg(long, value_parser = parse_size, value_delimiter = ',', default_value = "4K,64K,1M,16M")]
+    sizes: Vec<u64>,
+
+    /// buffer sizes to try with the streaming engine (comma separated)
+    #[arg(long, value_parser = parse_size, value_delimiter = ',', default_value = "64K,1M,8M")]
+    buffers: Vec<u64>,
+
+    /// numbers of files copied in parallel to try (comma separated)
+    #[arg(long, value_delimiter = ',', default_value = "1,4")]
+    jobs: Vec<usize>,
+}
+
+/// How a benchmark run moves the data
+#[derive(Clone, Copy)]
+enum Engine {
+    /// `fs::copy`, which lets the OS pick (copy_file_range, clonefile, CopyFileEx)
+    System,
+    /// Our own read/write loop with a buffer of this size, as used for streamed copies
+    Stream(usize),
+}
+
+struct Outcome {
+    engine: Engine,
+    jobs: usize,
+    elapsed: Duration,
+}
+
+/// Copy a generated workload to `target` with every engine, buffer size and
+/// job count, and report the throughput of each
+pub fn run(args: &BenchArgs) -> Result<()> {
+    if args.files == 0 || args.sizes.is_empty() || args.jobs.contains(&0) {
+        anyhow::bail!("the workload needs at least one file, one size and one job");
+    }
+    let scratch = args.target.join(format!("copro-bench-{}", std::process::id()));
+    let source = scratch.join("source");
+    fs::create_dir_all(&source).with_context(|| format!("could not create {}", scratch.display()))?;
+
+    let result = bench(args, &scratch, &source);
+    let _ = fs::remove_dir_all(&scratch);
+    result
+}
+
+fn bench(args: &BenchArgs, scratch: &Path, source: &Path) -> Result<()> {
+    let files = generate(source, args.files, &args.sizes)?;
+    let total_bytes: u64 = files.iter().map(|(_, size)| size).sum();
+    println!("{}{} {} files, {} on {}", icon!("🏁 "),
+        style("BENCH").cyan().bold(),
+        style(files.len()).yellow(),
+        style(format_size(total_bytes as f64)).green(),
+        style(args.target.display()).white()
+    );
+    println!("   {:<8} {:>8} {:>5} {:>9} {:>12}", "engine", "buffer", "jobs", "time", "throughput");
+
+    let mut engines = vec![Engine::System];
+    engines.extend(args.buffers.iter().map(|&size| Engine::Stream(size.max(1) as usize)));
+
+    let mut outcomes = Vec::new();
+    for &engine in &engines {
+        for &jobs in &args.jobs {
+            let destination = scratch.join("copy");
+            let _ = fs::remove_dir_all(&destination);
+            fs::create_dir_all(&destination)?;
+
+            let start = Instant::now();
+            copy_all(&files, source, &destination, engine, jobs)?;
+            let outcome = Outcome { engine, jobs, elapsed: start.elapsed() };
+            print_outcome(&outcome, total_bytes);
+            outcomes.push(outcome);
+            fs::remove_dir_all(&destination)?;
+        }
+    }
+
+    if let Some(best) = outcomes.iter().min_by_key(|o| o.elapsed) {
+        let (engine, buffer) = describe(best.engine);
+        println!("{}{} {} engine, {} buffer, {} jobs ({})", icon!("🏆 "),
+            style("Fastest:").green().bold(),
+            engine,
+            buffer,
+            best.jobs,
+            style(format_rate(total_bytes as f64 / best.elapsed.as_secs_f64().max(0.001))).green().bold()
+        );
+    }
+    Ok(())
+}
+
+/// Write `count` files of incompressible data, cycling through `sizes`
+fn generate(source: &Path, count: usize, sizes: &[u64]) -> io::Result<Vec<(PathBuf, u64)>> {
+    let mut block = vec![0u8; 1024 * 1024];
+    fastrand::fill(&mut block);
+    let mut files = Vec::with_capacity(count);
+    for i in 0..count {
+        let size = sizes[i % sizes.len()];
+        let path = source.join(format!("file-{i:05}.bin"));
+        let mut file = io::BufWriter::new(fs::File::create(&path)?);
+        let mut left = size;
+        while left > 0 {
+            let chunk = left.min(block.len() as u64) as usize;
+            file.write_all(&block[..chunk])?;
+            left -= chunk as u64;
+        }
+        file.flush()?;
+        files.push((path, size));
+    }
+    Ok(files)
+}
+
+/// Copy every file into `destination` with `jobs` workers, syncing each copy
+/// so the numbers reflect the device rather than the page cache
+fn copy_all(files: &[(PathBuf, u64)], source: &Path, destination: &Path, engine: Engine, jobs: usize) -> io::Result<()> {
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| scope.spawn(|| -> io::Result<()> {
+                let mut buffer = match engine {
+                    Engine::Stream(size) => vec![0u8; size],
+                    Engine::System => Vec::new(),
+                };
+                while let Some((file, _)) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let target = destination.join(file.strip_prefix(source).unwrap_or(file));
+                    match engine {
+                        Engine::System => {
+                            fs::copy(file, &target)?;
+                        }
+                        Engine::Stream(_) => {
+                            let mut reader = fs::File::open(file)?;
+                            let mut writer = fs::File::create(&target)?;
+                            loop {
+                                let read = reader.read(&mut buffer)?;
+                                if read == 0 {
+                                    break;
+                                }
+                                writer.write_all(&buffer[..read])?;
+                            }
+                        }
+                    }
+                    fs::File::open(&target)?.sync_all()?;
+                }
+                Ok(())
+            }))
+            .collect();
+        for worker in workers {
+            worker.join().unwrap_or_else(|_| Err(io::Error::other("benchmark worker panicked")))?;
+        }
+        Ok(())
+    })
+}
+
+fn describe(engine: Engine) -> (&'static str, String) {
+    match engine {
+        Engine::System => ("system", String::from("-")),
+        Engine::Stream(size) => ("stream", buffer_size(size)),
+    }
+}
+
+/// Buffer size the way it is passed on the command line, e.g. `64K`
+fn buffer_size(size: usize) -> String {
+    match size {
+        s if s % (1 << 20) == 0 => format!("{}M", s >> 20),
+        s if s % (1 << 10) == 0 => format!("{}K", s >> 10),
+        s => s.to_string(),
+    }
+}
+
+fn print_outcome(outcome: &Outcome, total_bytes: u64) {
+    let (engine, buffer) = describe(outcome.engine);
+    let rate = total_bytes as f64 / outcome.elapsed.as_secs_f64().max(0.001);
+    println!("   {:<8} {:>8} {:>5} {:>8.2}s {:>12}",
+        engine,
+        buffer,
+        outcome.jobs,
+        outcome.elapsed.as_secs_f32(),
+        style(format_rate(rate)).green()
+    );
+}
@@ -0,0 +1,215 @@
+use std::{
+    fs,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Bytes read and written at a time; large enough that a USB stick or an
+/// SD card sees whole erase blocks
+const BLOCK: usize = 4 * 1024 * 1024;
+
+/// `_IOR(0x12, 114, size_t)` from linux/fs.h: the size of a block device in bytes
+#[cfg(target_os = "linux")]
+const BLKGETSIZE64: libc::Ioctl = (0x8000_1272 | (std::mem::size_of::<libc::size_t>() as u32) << 16) as libc::Ioctl;
+
+/// Whether `path` is a block device: a whole drive (`/dev/sdb`) or one of
+/// its partitions
+pub fn is_block_device(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        fs::metadata(path).is_ok_and(|meta| meta.file_type().is_block_device())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Size in bytes of a block device, asked of the kernel (BLKGETSIZE64 on
+/// Linux), or of a regular file
+pub fn size(path: &Path) -> io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    if !is_block_device(path) {
+        return Ok(file.metadata()?.len());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        let mut bytes: u64 = 0;
+        // SAFETY: BLKGETSIZE64 writes one u64 to the pointer it is given
+        if unsafe { libc::ioctl(file.as_raw_fd(), BLKGETSIZE64, &mut bytes) } == 0 {
+            return Ok(bytes);
+        }
+    }
+    // Elsewhere a device reports its size as the end of the stream
+    file.seek(SeekFrom::End(0))
+}
+
+/// Where `device`, or one of its partitions, is mounted
+#[cfg(target_os = "linux")]
+pub fn mount_point(device: &Path) -> Option<PathBuf> {
+    use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+    let sys = |rdev: u64| fs::canonicalize(format!("/sys/dev/block/{}:{}", libc::major(rdev), libc::minor(rdev))).ok();
+    let rdev = fs::metadata(device).ok()?.rdev();
+    let disk = sys(rdev);
+    let mounts = fs::read_to_string("/proc/self/mounts").ok()?;
+    mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let (source, target) = (fields.next()?, fields.next()?);
+        let mounted = fs::metadata(source).ok().filter(|meta| meta.file_type().is_block_device())?.rdev();
+        let partition_of_device = || sys(mounted).and_then(|path| path.parent().map(Path::to_path_buf)) == disk;
+        (mounted == rdev || (disk.is_some() && partition_of_device())).then(|| PathBuf::from(unescape(target)))
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn mount_point(_device: &Path) -> Option<PathBuf> {
+    None
+}
+
+/// Copy the first `length` bytes of `source` to `destination`, block by
+/// block, no faster than `limit` bytes per second. A device destination is
+/// written in place; a file is created or truncated. Everything is flushed
+/// to the medium before this returns.
+pub fn copy(
+    source: &Path,
+    destination: &Path,
+    length: u64,
+    limit: Option<u64>,
+    counter: &AtomicU64,
+    interrupted: &AtomicBool,
+) -> io::Result<u64> {
+    let mut reader = fs::File::open(source)?.take(length);
+    let to_device = is_block_device(destination);
+    let mut writer = fs::OpenOptions::new()
+        .write(true)
+        .create(!to_device)
+        .truncate(!to_device)
+        .open(destination)?;
+
+    let started = Instant::now();
+    let mut buffer = vec![0u8; BLOCK];
+    let mut written = 0u64;
+    loop {
+        if interrupted.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "copy interrupted"));
+        }
+        let read = read_block(&mut reader, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        written += read as u64;
+        counter.fetch_add(read as u64, Ordering::Relaxed);
+
+        // Stay behind the schedule --bwlimit sets, in short naps so Ctrl+C is not kept waiting
+        if let Some(limit) = limit.filter(|limit| *limit > 0) {
+            let due = Duration::from_secs_f64(written as f64 / limit as f64);
+            while let Some(ahead) = due.checked_sub(started.elapsed()).filter(|_| !interrupted.load(Ordering::Relaxed)) {
+                thread::sleep(ahead.min(Duration::from_millis(100)));
+            }
+        }
+    }
+    if written < length {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("source ended after {written} of {length} bytes")));
+    }
+    writer.sync_all()?;
+    Ok(written)
+}
+
+/// Where a written image differs from its source
+pub struct Difference {
+    /// Offset of the first byte that differs
+    pub first: u64,
+    /// Blocks (of 4 MiB) with at least one differing byte
+    pub blocks: u64,
+}
+
+/// Read the first `length` bytes of `source` and `destination` back block by
+/// block and compare them. The destination is dropped from the page cache
+/// first, so what is compared is what reached the medium rather than what
+/// the kernel still remembers writing. `counter` grows by the bytes compared.
+pub fn compare(
+    source: &Path,
+    destination: &Path,
+    length: u64,
+    counter: &AtomicU64,
+    interrupted: &AtomicBool,
+) -> io::Result<Option<Difference>> {
+    let mut expected = fs::File::open(source)?.take(length);
+    let written = fs::File::open(destination)?;
+    drop_cache(&written);
+    let mut actual = written.take(length);
+
+    let mut left = vec![0u8; BLOCK];
+    let mut right = vec![0u8; BLOCK];
+    let mut offset = 0u64;
+    let mut difference: Option<Difference> = None;
+    loop {
+        if interrupted.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "verification interrupted"));
+        }
+        let read = read_block(&mut expected, &mut left)?;
+        if read == 0 {
+            break;
+        }
+        let got = read_block(&mut actual, &mut right)?;
+        if got < read {
+            // The destination ends early: everything from there on differs
+            let first = difference.as_ref().map_or(offset + got as u64, |d| d.first);
+            let blocks = difference.as_ref().map_or(0, |d| d.blocks) + (length - offset).div_ceil(BLOCK as u64);
+            return Ok(Some(Difference { first, blocks }));
+        }
+        if left[..read] != right[..read] {
+            let at = left[..read].iter().zip(&right[..read]).position(|(a, b)| a != b).unwrap_or(0);
+            match &mut difference {
+                Some(difference) => difference.blocks += 1,
+                None => difference = Some(Difference { first: offset + at as u64, blocks: 1 }),
+            }
+        }
+        offset += read as u64;
+        counter.fetch_add(read as u64, Ordering::Relaxed);
+    }
+    Ok(difference)
+}
+
+/// Ask the kernel to forget the cached pages of `file`, which has been
+/// synced, so reading it goes to the device
+fn drop_cache(file: &fs::File) {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        // SAFETY: posix_fadvise only reads its arguments; a failure merely leaves the cache as it is
+        unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = file;
+}
+
+/// Fill `buffer` as far as the reader allows, so every write but the last
+/// is a whole block
+fn read_block(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// A mount point as /proc/self/mounts writes it, with spaces and tabs as
+/// octal escapes
+#[cfg(target_os = "linux")]
+pub fn unescape(field: &str) -> String {
+    field.replace("\\040", " ").replace("\\011", "\t").replace("\\012", "\n").replace("\\134", "\\")
+}
@@ -0,0 +1,311 @@
+use crate::state::state_dir;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The catalog `--catalog` writes to when it is given no file
+pub fn default_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("catalog.db"))
+}
+
+/// What became of one file in a run
+#[derive(Clone, Copy)]
+#[cfg_attr(not(feature = "catalog"), allow(dead_code))]
+pub enum Outcome<'a> {
+    Copied { hash: Option<blake3::Hash> },
+    /// Copied while the source changed
+    Unstable,
+    Skipped(&'a str),
+    Failed(&'a str),
+}
+
+/// Totals of a run, written to its row when it ends
+#[cfg_attr(not(feature = "catalog"), allow(dead_code))]
+pub struct Summary<'a> {
+    /// `completed`, or why the run stopped early
+    pub status: &'a str,
+    pub copied: usize,
+    pub failed: usize,
+    pub bytes: u64,
+}
+
+/// A run as `copro history` lists it
+#[cfg_attr(not(feature = "catalog"), allow(dead_code))]
+pub struct RunRow {
+    pub id: i64,
+    /// Local time the run started, `YYYY-MM-DD HH:MM:SS`
+    pub started: String,
+    pub source: String,
+    pub destination: String,
+    /// `running` for a run that has not finished (or was killed)
+    pub status: String,
+    pub copied: u64,
+    pub failed: u64,
+    pub bytes: u64,
+}
+
+/// One file of one run
+#[cfg_attr(not(feature = "catalog"), allow(dead_code))]
+pub struct FileRow {
+    pub run: i64,
+    pub started: String,
+    pub source: String,
+    pub destination: String,
+    pub result: String,
+    pub size: u64,
+    pub hash: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A source that failed in several runs
+#[cfg_attr(not(feature = "catalog"), allow(dead_code))]
+pub struct FailingRow {
+    pub source: String,
+    pub runs: u64,
+    /// Error of the latest failure, and when that run started
+    pub error: String,
+    pub last: String,
+}
+
+/// Every run recorded with --catalog: one row per run with its command line
+/// and totals, and one per file with its result, size, hash and how long it
+/// took. Rows are written as the run goes, so a run that is killed still
+/// leaves what it did.
+#[cfg(feature = "catalog")]
+pub struct Catalog {
+    connection: rusqlite::Connection,
+    run: i64,
+}
+
+#[cfg(feature = "catalog")]
+const SCHEMA: &str = "
+    PRAGMA journal_mode = WAL;
+    PRAGMA synchronous = NORMAL;
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        started INTEGER NOT NULL,
+        finished INTEGER,
+        source TEXT NOT NULL,
+        destination TEXT NOT NULL,
+        command TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'running',
+        copied INTEGER NOT NULL DEFAULT 0,
+        failed INTEGER NOT NULL DEFAULT 0,
+        bytes INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS files (
+        run INTEGER NOT NULL REFERENCES runs(id),
+        source TEXT NOT NULL,
+        destination TEXT NOT NULL,
+        result TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        hash TEXT,
+        error TEXT,
+        seconds REAL NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS files_source ON files(source);
+    CREATE INDEX IF NOT EXISTS files_destination ON files(destination);
+";
+
+#[cfg(feature = "catalog")]
+impl Catalog {
+    /// Open (creating it if needed) the catalog at `path` and add a row for
+    /// the run copying `source` to `destination`
+    pub fn start(path: &Path, source: &Path, destination: &Path) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let connection = rusqlite::Connection::open(path).map_err(io::Error::other)?;
+        connection.execute_batch(SCHEMA).map_err(io::Error::other)?;
+        let command = std::env::args_os().map(|arg| arg.to_string_lossy().into_owned()).collect::<Vec<_>>().join(" ");
+        connection
+            .execute(
+                "INSERT INTO runs (started, source, destination, command) VALUES (?1, ?2, ?3, ?4)",
+                (unix_now(), text(source), text(destination), command),
+            )
+            .map_err(io::Error::other)?;
+        let run = connection.last_insert_rowid();
+        Ok(Self { connection, run })
+    }
+
+    /// Record what became of `source`, written (or not) to `destination`
+    pub fn record(&self, source: &Path, destination: &Path, size: u64, seconds: f64, outcome: Outcome) -> io::Result<()> {
+        let (result, hash, error) = match outcome {
+            Outcome::Copied { hash } => ("copied", hash.map(|hash| hash.to_hex().to_string()), None),
+            Outcome::Unstable => ("unstable", None, Some("changed while being copied")),
+            Outcome::Skipped(reason) => ("skipped", None, Some(reason)),
+            Outcome::Failed(error) => ("failed", None, Some(error)),
+        };
+        self.connection
+            .execute(
+                "INSERT INTO files (run, source, destination, result, size, hash, error, seconds)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                (self.run, text(source), text(destination), result, size as i64, hash, error, seconds),
+            )
+            .map(|_| ())
+            .map_err(io::Error::other)
+    }
+
+    /// Close the run's row with its totals
+    pub fn finish(self, summary: Summary) -> io::Result<()> {
+        self.connection
+            .execute(
+                "UPDATE runs SET finished = ?1, status = ?2, copied = ?3, failed = ?4, bytes = ?5 WHERE id = ?6",
+                (unix_now(), summary.status, summary.copied as i64, summary.failed as i64, summary.bytes as i64, self.run),
+            )
+            .map(|_| ())
+            .map_err(io::Error::other)
+    }
+}
+
+/// Open an existing catalog for reading
+#[cfg(feature = "catalog")]
+fn open(path: &Path) -> io::Result<rusqlite::Connection> {
+    rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(io::Error::other)
+}
+
+/// The latest `limit` runs, newest first
+#[cfg(feature = "catalog")]
+pub fn runs(path: &Path, limit: usize) -> io::Result<Vec<RunRow>> {
+    let connection = open(path)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT id, datetime(started, 'unixepoch', 'localtime'), source, destination, status, copied, failed, bytes
+             FROM runs ORDER BY id DESC LIMIT ?1",
+        )
+        .map_err(io::Error::other)?;
+    let rows = statement
+        .query_map([limit as i64], |row| {
+            Ok(RunRow {
+                id: row.get(0)?,
+                started: row.get(1)?,
+                source: row.get(2)?,
+                destination: row.get(3)?,
+                status: row.get(4)?,
+                copied: row.get(5)?,
+                failed: row.get(6)?,
+                bytes: row.get(7)?,
+            })
+        })
+        .map_err(io::Error::other)?;
+    rows.collect::<Result<_, _>>().map_err(io::Error::other)
+}
+
+/// What the latest `limit` runs did with `file`, as source or destination, newest first
+#[cfg(feature = "catalog")]
+pub fn file_history(path: &Path, file: &Path, limit: usize) -> io::Result<Vec<FileRow>> {
+    let connection = open(path)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT files.run, datetime(runs.started, 'unixepoch', 'localtime'), files.source, files.destination,
+                    files.result, files.size, files.hash, files.error
+             FROM files JOIN runs ON runs.id = files.run
+             WHERE files.source = ?1 OR files.destination = ?1
+             ORDER BY files.run DESC, files.rowid DESC LIMIT ?2",
+        )
+        .map_err(io::Error::other)?;
+    let rows = statement
+        .query_map((text(file), limit as i64), |row| {
+            Ok(FileRow {
+                run: row.get(0)?,
+                started: row.get(1)?,
+                source: row.get(2)?,
+                destination: row.get(3)?,
+                result: row.get(4)?,
+                size: row.get(5)?,
+                hash: row.get(6)?,
+                error: row.get(7)?,
+            })
+        })
+        .map_err(io::Error::other)?;
+    rows.collect::<Result<_, _>>().map_err(io::Error::other)
+}
+
+/// Sources that failed in at least `min_runs` runs, the most often failing first
+#[cfg(feature = "catalog")]
+pub fn failing(path: &Path, min_runs: usize, limit: usize) -> io::Result<Vec<FailingRow>> {
+    let connection = open(path)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT files.source, COUNT(DISTINCT files.run), MAX(files.run)
+             FROM files WHERE files.result = 'failed'
+             GROUP BY files.source HAVING COUNT(DISTINCT files.run) >= ?1
+             ORDER BY COUNT(DISTINCT files.run) DESC, MAX(files.run) DESC LIMIT ?2",
+        )
+        .map_err(io::Error::other)?;
+    let failing = statement
+        .query_map((min_runs as i64, limit as i64), |row| Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?, row.get::<_, i64>(2)?)))
+        .map_err(io::Error::other)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io::Error::other)?;
+
+    let mut latest = connection
+        .prepare(
+            "SELECT COALESCE(files.error, ''), datetime(runs.started, 'unixepoch', 'localtime')
+             FROM files JOIN runs ON runs.id = files.run
+             WHERE files.source = ?1 AND files.run = ?2 AND files.result = 'failed' LIMIT 1",
+        )
+        .map_err(io::Error::other)?;
+    failing
+        .into_iter()
+        .map(|(source, runs, run)| {
+            let (error, last) = latest
+                .query_row((&source, run), |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(io::Error::other)?;
+            Ok(FailingRow { source, runs, error, last })
+        })
+        .collect()
+}
+
+/// Builds without the `catalog` feature cannot record runs
+#[cfg(not(feature = "catalog"))]
+pub struct Catalog;
+
+#[cfg(not(feature = "catalog"))]
+impl Catalog {
+    pub fn start(_path: &Path, _source: &Path, _destination: &Path) -> io::Result<Self> {
+        Err(unsupported())
+    }
+
+    pub fn record(&self, _source: &Path, _destination: &Path, _size: u64, _seconds: f64, _outcome: Outcome) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub fn finish(self, _summary: Summary) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "catalog"))]
+fn unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "this build has no run catalog (the catalog feature is off)")
+}
+
+#[cfg(not(feature = "catalog"))]
+pub fn runs(_path: &Path, _limit: usize) -> io::Result<Vec<RunRow>> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "catalog"))]
+pub fn file_history(_path: &Path, _file: &Path, _limit: usize) -> io::Result<Vec<FileRow>> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "catalog"))]
+pub fn failing(_path: &Path, _min_runs: usize, _limit: usize) -> io::Result<Vec<FailingRow>> {
+    Err(unsupported())
+}
+
+/// Paths are stored absolute, however the run named them
+#[cfg(feature = "catalog")]
+fn text(path: &Path) -> String {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()).to_string_lossy().into_owned()
+}
+
+#[cfg(feature = "catalog")]
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64)
+}
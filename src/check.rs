@@ -0,0 +1,112 @@
+use crate::manifest::{hash_file, EntryResult, Manifest};
+use anyhow::{Context, Result};
+use console::style;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Arguments of `copro check`
+#[derive(clap::Args, Clone)]
+pub struct CheckArgs {
+    /// manifest file to check against
+    manifest: PathBuf,
+
+    /// destination root to check, if the copy has moved since the manifest was written
+    #[arg(long)]
+    root: Option<PathBuf>,
+
+    /// also list files that match
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+enum Drift {
+    Missing,
+    Size(u64, u64),
+    Hash,
+    Unreadable(String),
+}
+
+/// Check every copied entry for existence, size and content drift.
+/// Returns whether the destination matches the manifest.
+pub fn run(args: &CheckArgs) -> Result<bool> {
+    let manifest = Manifest::load(&args.manifest)
+        .with_context(|| format!("could not read manifest {}", args.manifest.display()))?;
+
+    println!("{}{} {}", icon!("🔍 "),
+        style(tr!("CHECKING")).cyan().bold(),
+        tr!("{destination} against {manifest}",
+            destination = style(args.root.as_deref().unwrap_or(&manifest.destination).display()).white(),
+            manifest = style(args.manifest.display()).white()
+        )
+    );
+
+    let mut checked = 0usize;
+    let mut drifted = 0usize;
+    for entry in manifest.entries.iter().filter(|e| e.result == EntryResult::Copied) {
+        let path = relocate(&entry.destination, &manifest.destination, args.root.as_deref());
+        checked += 1;
+
+        match drift(&path, entry.size, entry.hash.as_deref()) {
+            None => {
+                if args.verbose {
+                    println!("{} {}", style(icon!("✅ ", "OK:")).green().bold(), style(path.display()).white());
+                }
+            }
+            Some(problem) => {
+                drifted += 1;
+                let detail = match problem {
+                    Drift::Missing => String::from(tr!("missing")),
+                    Drift::Size(expected, actual) => tr!("size changed: {expected} → {actual} bytes", expected = expected, actual = actual),
+                    Drift::Hash => String::from(tr!("content changed (hash mismatch)")),
+                    Drift::Unreadable(e) => tr!("unreadable: {error}", error = e),
+                };
+                println!("{} {} ({})",
+                    style(icon!("❌ ", "Drift:")).red().bold(),
+                    style(path.display()).white(),
+                    style(detail).red()
+                );
+            }
+        }
+    }
+
+    if drifted == 0 {
+        println!("{}{} {}", icon!("🎉 "),
+            style(tr!("OK!")).green().bold(),
+            tr!("{count} files match the manifest", count = style(checked).cyan().bold())
+        );
+    } else {
+        println!("{}{} {}", icon!("⚠️  "),
+            style(tr!("DRIFT!")).red().bold(),
+            tr!("{count} of {total} files differ from the manifest", count = style(drifted).red().bold(), total = style(checked).cyan())
+        );
+    }
+    Ok(drifted == 0)
+}
+
+/// Map a recorded destination path onto `root` when the copy has moved
+fn relocate(path: &Path, recorded_root: &Path, root: Option<&Path>) -> PathBuf {
+    match (root, path.strip_prefix(recorded_root)) {
+        (Some(root), Ok(relative)) if relative.as_os_str().is_empty() => root.to_path_buf(),
+        (Some(root), Ok(relative)) => root.join(relative),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn drift(path: &Path, size: u64, hash: Option<&str>) -> Option<Drift> {
+    let meta = match fs::metadata(path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Some(Drift::Missing),
+        Err(e) => return Some(Drift::Unreadable(e.to_string())),
+    };
+    if meta.len() != size {
+        return Some(Drift::Size(size, meta.len()));
+    }
+    let expected = hash?;
+    match hash_file(path) {
+        Ok(actual) if actual == expected => None,
+        Ok(_) => Some(Drift::Hash),
+        Err(e) => Some(Drift::Unreadable(e.to_string())),
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Sidecar `.part` file recording how much of a large streamed copy has been
+/// durably written, so a resumed run can continue exactly without rehashing.
+pub struct Checkpoint {
+    path: PathBuf,
+    identity: String,
+    pub chunk_size: u64,
+}
+
+impl Checkpoint {
+    /// Checkpoint for copying `range` of `source` into the file at `target`
+    pub fn new(source: &Path, target: &Path, (offset, length): (u64, u64), chunk_size: u64) -> io::Result<Self> {
+        let meta = fs::metadata(source)?;
+        let mtime = meta
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let identity = format!(
+            "source={}\nsize={}\nmtime={}\noffset={}\nlength={}\nchunk_size={}\n",
+            fs::canonicalize(source)?.display(),
+            meta.len(),
+            mtime,
+            offset,
+            length,
+            chunk_size
+        );
+
+        let mut path = target.as_os_str().to_owned();
+        path.push(".part");
+        Ok(Self {
+            path: PathBuf::from(path),
+            identity,
+            chunk_size,
+        })
+    }
+
+    /// Bytes that can be kept from a previous run: the last recorded checkpoint,
+    /// provided it was made for the same source and `target` still holds that much.
+    /// `None` when there is no usable state.
+    pub fn resume_offset(&self, target: &Path) -> Option<u64> {
+        let state = fs::read_to_string(&self.path).ok()?;
+        let completed = state.strip_prefix(&self.identity)?.trim().strip_prefix("completed=")?;
+        let completed: u64 = completed.parse().ok()?;
+        let existing = fs::metadata(target).ok()?.len();
+        (existing >= completed).then_some(completed)
+    }
+
+    /// Record that the first `completed` bytes of the target are durable
+    pub fn record(&self, completed: u64) -> io::Result<()> {
+        let mut staging = self.path.as_os_str().to_owned();
+        staging.push(".new");
+        let staging = PathBuf::from(staging);
+        fs::write(&staging, format!("{}completed={}\n", self.identity, completed))?;
+        fs::rename(&staging, &self.path)
+    }
+
+    pub fn remove(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{merge::Sources, output_path, temp_path, walk};
+use anyhow::{bail, Context, Result};
+use console::style;
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Arguments of `copro clean`
+#[derive(clap::Args, Clone)]
+pub struct CleanArgs {
+    /// source of the interrupted copy
+    source: PathBuf,
+
+    /// destination of the interrupted copy, as given to copro cp
+    destination: PathBuf,
+
+    /// list what would be removed without removing it
+    #[arg(long)]
+    list_only: bool,
+}
+
+/// Remove what an interrupted copy of `source` to `destination` left behind:
+/// the temporary file each copy is written to before it is renamed into
+/// place, and the `.part` checkpoints of --resume. Only names a copy of this
+/// source would use are touched, so a user's own `.tmp` files stay.
+pub fn run(args: &CleanArgs) -> Result<()> {
+    if !args.source.exists() {
+        bail!("source path does not exist: {}", args.source.display());
+    }
+    let sources = Sources::single(args.source.clone());
+    let notes = walk::Notes::default();
+    let destinations = walk::Walker::new(&args.source, None, walk::BrokenLinks::Skip, &notes)
+        .map(|file| file.map(|file| output_path(&file.path, &sources, &args.destination, None, false)))
+        .collect::<io::Result<HashSet<_>>>()
+        .with_context(|| format!("could not read {}", args.source.display()))?;
+    // `notes.tmp` is the temporary file of `notes`, unless the source has a `notes.tmp` of its own
+    let mut leftovers: Vec<_> = destinations
+        .iter()
+        .flat_map(|destination| sidecars(destination))
+        .filter(|path| !destinations.contains(path) && path.is_file())
+        .collect();
+    leftovers.sort();
+
+    let mut removed = 0;
+    for path in &leftovers {
+        if args.list_only {
+            println!("{} {}", style(icon!("🧹 ", "Would remove:")).cyan(), style(path.display()).white());
+            continue;
+        }
+        match fs::remove_file(path) {
+            Ok(()) => {
+                removed += 1;
+                println!("{} {}", style(icon!("🧹 ", "Removed:")).green(), style(path.display()).white());
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => println!("{} {} ({})", style(icon!("❌ ", "Failed:")).red().bold(), style(path.display()).white(), style(e).red()),
+        }
+    }
+
+    if leftovers.is_empty() {
+        println!("{}{}", icon!("✨ "), tr!("Nothing to clean in {path}", path = style(args.destination.display()).white()));
+    } else if args.list_only {
+        println!("{}{}", icon!("📋 "), tr!("{count} leftover files would be removed", count = style(leftovers.len()).cyan().bold()));
+    } else {
+        println!("{}{}", icon!("✨ "), tr!("{count} leftover files removed", count = style(removed).cyan().bold()));
+    }
+    Ok(())
+}
+
+/// Files a copy to `destination` may leave when it is stopped
+fn sidecars(destination: &Path) -> [PathBuf; 3] {
+    let with_suffix = |suffix: &str| {
+        let mut path = destination.as_os_str().to_owned();
+        path.push(suffix);
+        PathBuf::from(path)
+    };
+    [temp_path(destination), with_suffix(".part"), with_suffix(".part.new")]
+}
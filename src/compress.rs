@@ -0,0 +1,58 @@
+use crate::pipe::{pump, CountingReader};
+use std::{
+    fs,
+    io::{self, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64},
+};
+
+/// Compression formats understood by `--decompress`
+#[derive(Clone, Copy)]
+pub enum Codec {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Codec {
+    /// Codec implied by the file extension, if any
+    pub fn for_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gz" => Some(Codec::Gzip),
+            "zst" => Some(Codec::Zstd),
+            "xz" => Some(Codec::Xz),
+            _ => None,
+        }
+    }
+}
+
+/// Destination name with the compression extension dropped
+pub fn output_path(destination: PathBuf) -> PathBuf {
+    match Codec::for_path(&destination) {
+        Some(_) => destination.with_extension(""),
+        None => destination,
+    }
+}
+
+/// Decompress `source` into `target`, counting compressed bytes read.
+/// Returns the number of decompressed bytes written.
+pub fn decompress_file(
+    source: &Path,
+    target: &Path,
+    codec: Codec,
+    counter: &AtomicU64,
+    interrupted: &AtomicBool,
+) -> io::Result<u64> {
+    let input = BufReader::new(CountingReader { inner: crate::noatime::open(source)?, counter });
+    let reader: Box<dyn Read + '_> = match codec {
+        // Multi-member archives (e.g. from pigz or concatenated .gz files) are common
+        Codec::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(input)),
+        Codec::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(input)?),
+        Codec::Xz => Box::new(xz2::bufread::XzDecoder::new_multi_decoder(input)),
+    };
+
+    let mut output = io::BufWriter::new(fs::File::create(target)?);
+    let written = pump(reader, &mut output, interrupted)?;
+    output.flush()?;
+    Ok(written)
+}
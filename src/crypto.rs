@@ -0,0 +1,84 @@
+use crate::pipe::{pump, CountingReader};
+use age::{x25519, Identity, IdentityFile, Recipient};
+use std::{
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicBool, AtomicU64},
+};
+
+/// Encryption applied to each file as it is written, or removed from it
+pub enum Crypto {
+    Encrypt(Vec<x25519::Recipient>),
+    Decrypt(Vec<Box<dyn Identity>>),
+}
+
+impl Crypto {
+    pub fn encrypt(recipients: &[String]) -> Result<Self, String> {
+        recipients
+            .iter()
+            .map(|r| x25519::Recipient::from_str(r).map_err(|e| format!("invalid recipient {}: {}", r, e)))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Crypto::Encrypt)
+    }
+
+    pub fn decrypt(identity_file: &Path) -> Result<Self, String> {
+        IdentityFile::from_file(identity_file.to_string_lossy().into_owned())
+            .map_err(|e| e.to_string())?
+            .into_identities()
+            .map(Crypto::Decrypt)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Destination name for an output file: `.age` is added when encrypting
+    /// and stripped when decrypting
+    pub fn output_path(&self, destination: PathBuf) -> PathBuf {
+        match self {
+            Crypto::Encrypt(_) => {
+                let mut name = OsString::from(destination.as_os_str());
+                name.push(".age");
+                PathBuf::from(name)
+            }
+            Crypto::Decrypt(_) if destination.extension().is_some_and(|ext| ext == "age") => {
+                destination.with_extension("")
+            }
+            Crypto::Decrypt(_) => destination,
+        }
+    }
+
+    /// Encrypt or decrypt `source` into `target`, counting source bytes read.
+    /// Returns the number of bytes written.
+    pub fn transform(
+        &self,
+        source: &Path,
+        target: &Path,
+        counter: &AtomicU64,
+        interrupted: &AtomicBool,
+    ) -> io::Result<u64> {
+        let input = CountingReader { inner: crate::noatime::open(source)?, counter };
+        let output = io::BufWriter::new(fs::File::create(target)?);
+
+        match self {
+            Crypto::Encrypt(recipients) => {
+                let encryptor = age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn Recipient))
+                    .map_err(io::Error::other)?;
+                let mut writer = encryptor.wrap_output(output)?;
+                let written = pump(input, &mut writer, interrupted)?;
+                writer.finish()?.flush()?;
+                Ok(written)
+            }
+            Crypto::Decrypt(identities) => {
+                let decryptor = age::Decryptor::new(io::BufReader::new(input)).map_err(io::Error::other)?;
+                let reader = decryptor
+                    .decrypt(identities.iter().map(|i| i.as_ref() as &dyn Identity))
+                    .map_err(io::Error::other)?;
+                let mut output = output;
+                let written = pump(reader, &mut output, interrupted)?;
+                output.flush()?;
+                Ok(written)
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+/// The local date and time a run started, for destinations such as
+/// `/backups/%Y-%m-%d` that name a new folder every day
+pub struct Now {
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    /// Day of the year, from 1
+    yday: u32,
+}
+
+impl Now {
+    #[cfg(unix)]
+    pub fn local() -> Self {
+        // SAFETY: time accepts a null pointer; localtime_r fills the zeroed tm
+        // it is given and keeps no reference to it
+        let tm = unsafe {
+            let time = libc::time(std::ptr::null_mut());
+            let mut tm: libc::tm = std::mem::zeroed();
+            libc::localtime_r(&time, &mut tm);
+            tm
+        };
+        Self {
+            year: tm.tm_year + 1900,
+            month: tm.tm_mon as u32 + 1,
+            day: tm.tm_mday as u32,
+            hour: tm.tm_hour as u32,
+            minute: tm.tm_min as u32,
+            second: tm.tm_sec as u32,
+            yday: tm.tm_yday as u32 + 1,
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn local() -> Self {
+        use windows_sys::Win32::{Foundation::SYSTEMTIME, System::SystemInformation::GetLocalTime};
+
+        // SAFETY: GetLocalTime only writes the SYSTEMTIME it is given
+        let time = unsafe {
+            let mut time: SYSTEMTIME = std::mem::zeroed();
+            GetLocalTime(&mut time);
+            time
+        };
+        let (year, month, day) = (i32::from(time.wYear), u32::from(time.wMonth), u32::from(time.wDay));
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let before: u32 = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+            .iter()
+            .take(month as usize - 1)
+            .sum();
+        Self {
+            year,
+            month,
+            day,
+            hour: u32::from(time.wHour),
+            minute: u32::from(time.wMinute),
+            second: u32::from(time.wSecond),
+            yday: before + day,
+        }
+    }
+
+    /// `path` with its placeholders filled in: `%Y` year, `%y` two-digit
+    /// year, `%m` month, `%d` day, `%j` day of the year, `%H` hour, `%M`
+    /// minute, `%S` second, `%F` for `%Y-%m-%d`, `%T` for `%H:%M:%S` and `%%`
+    /// for a percent sign. Anything else is left as it is.
+    pub fn expand(&self, path: &Path) -> PathBuf {
+        let Some(template) = path.to_str().filter(|template| template.contains('%')) else {
+            return path.to_path_buf();
+        };
+        let mut out = String::with_capacity(template.len());
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => out.push_str(&self.year.to_string()),
+                Some('y') => out.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => out.push_str(&format!("{:02}", self.month)),
+                Some('d') => out.push_str(&format!("{:02}", self.day)),
+                Some('j') => out.push_str(&format!("{:03}", self.yday)),
+                Some('H') => out.push_str(&format!("{:02}", self.hour)),
+                Some('M') => out.push_str(&format!("{:02}", self.minute)),
+                Some('S') => out.push_str(&format!("{:02}", self.second)),
+                Some('F') => out.push_str(&format!("{}-{:02}-{:02}", self.year, self.month, self.day)),
+                Some('T') => out.push_str(&format!("{:02}:{:02}:{:02}", self.hour, self.minute, self.second)),
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+        PathBuf::from(out)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::pipe::{CountingReader, HashingReader};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+/// Chunks buffered per destination before the reader waits for a slow drive
+const QUEUE_DEPTH: usize = 4;
+
+/// Read `source` once and write it to every file in `targets` in parallel,
+/// counting source bytes read and feeding them to `hasher`. Each target is
+/// given the permissions of `source`, as fs::copy does. Fails if any
+/// destination fails.
+pub fn fan_out(
+    source: &Path,
+    targets: &[PathBuf],
+    counter: &AtomicU64,
+    interrupted: &AtomicBool,
+    hasher: Option<&mut blake3::Hasher>,
+) -> io::Result<u64> {
+    let file = crate::noatime::open(source)?;
+    let permissions = file.metadata()?.permissions();
+    let mut reader = HashingReader {
+        inner: CountingReader { inner: file, counter },
+        hasher,
+    };
+
+    thread::scope(|scope| {
+        let mut senders = Vec::with_capacity(targets.len());
+        let mut writers = Vec::with_capacity(targets.len());
+        for target in targets {
+            let (tx, rx) = mpsc::sync_channel::<Arc<Vec<u8>>>(QUEUE_DEPTH);
+            senders.push(tx);
+            let permissions = permissions.clone();
+            writers.push(scope.spawn(move || -> io::Result<()> {
+                let mut output = io::BufWriter::new(fs::File::create(target)?);
+                for chunk in rx {
+                    output.write_all(&chunk)?;
+                }
+                output.flush()?;
+                output.get_ref().set_permissions(permissions)
+            }));
+        }
+
+        let mut total = 0u64;
+        let read_result = loop {
+            if interrupted.load(Ordering::Relaxed) {
+                break Err(io::Error::new(io::ErrorKind::Interrupted, "copy interrupted"));
+            }
+            let mut buffer = vec![0u8; 1024 * 1024];
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break Ok(total),
+                Ok(read) => read,
+                Err(e) => break Err(e),
+            };
+            buffer.truncate(read);
+            total += read as u64;
+
+            let chunk = Arc::new(buffer);
+            // A writer that hung up has failed; its error is reported below
+            if senders.iter().any(|tx| tx.send(Arc::clone(&chunk)).is_err()) {
+                break Ok(total);
+            }
+        };
+        drop(senders);
+
+        for (writer, target) in writers.into_iter().zip(targets) {
+            let result = writer.join().unwrap_or_else(|_| Err(io::Error::other("writer thread panicked")));
+            if let Err(e) = result {
+                return Err(io::Error::new(e.kind(), format!("{}: {}", target.display(), e)));
+            }
+        }
+        read_result
+    })
+}
@@ -0,0 +1,115 @@
+use crate::state::{mtime_nanos, state_dir};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Entries not used for this long are dropped when the cache is saved
+const FORGET_AFTER_DAYS: u64 = 90;
+
+/// A hash as it was when the file was read
+#[derive(Serialize, Deserialize, Clone)]
+struct Entry {
+    size: u64,
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: u64,
+    inode: u64,
+    /// BLAKE3, hex encoded
+    hash: String,
+    /// Day (since the Unix epoch) the entry was last used
+    seen: u64,
+}
+
+/// Hashes computed by earlier runs, so repeated syncs of mostly unchanged
+/// trees do not read everything again. An entry holds while the file keeps
+/// the size, modification time and inode it had when it was hashed.
+pub struct HashCache {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+fn cache_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("hashes.json"))
+}
+
+impl HashCache {
+    /// The cache left by earlier runs; empty when there is none or it cannot be read
+    pub fn load() -> Self {
+        let entries = cache_file()
+            .and_then(|file| fs::read(file).ok())
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Self { entries: Mutex::new(entries) }
+    }
+
+    /// The hash of `path`, if it has not changed since it was cached
+    pub fn get(&self, path: &Path) -> Option<blake3::Hash> {
+        let (key, meta) = identify(path)?;
+        let mut entries = self.entries.lock().ok()?;
+        let entry = entries.get_mut(&key)?;
+        if (entry.size, entry.modified, entry.inode) != (meta.len(), mtime_nanos(&meta), inode(&meta)) {
+            return None;
+        }
+        entry.seen = today();
+        blake3::Hash::from_hex(&entry.hash).ok()
+    }
+
+    /// Remember `hash` as the content of `path` as it is now
+    pub fn insert(&self, path: &Path, hash: blake3::Hash) {
+        let Some((key, meta)) = identify(path) else {
+            return;
+        };
+        let entry = Entry {
+            size: meta.len(),
+            modified: mtime_nanos(&meta),
+            inode: inode(&meta),
+            hash: hash.to_hex().to_string(),
+            seen: today(),
+        };
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key, entry);
+        }
+    }
+
+    /// Write the cache for the next run, leaving out entries unused for a long time
+    pub fn save(&self) -> io::Result<()> {
+        let file = cache_file().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory (HOME is not set)"))?;
+        let mut entries = self.entries.lock().map_err(|_| io::Error::other("hash cache poisoned"))?;
+        let oldest = today().saturating_sub(FORGET_AFTER_DAYS);
+        entries.retain(|_, entry| entry.seen >= oldest);
+        let content = serde_json::to_vec(&*entries).map_err(io::Error::other)?;
+
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Written aside and renamed, so a crash cannot leave half a cache
+        let temp = file.with_extension("json.tmp");
+        fs::write(&temp, content)?;
+        fs::rename(&temp, &file)
+    }
+}
+
+/// The cache key of `path` and its current metadata; paths that are not
+/// valid Unicode are not cached
+fn identify(path: &Path) -> Option<(String, fs::Metadata)> {
+    let meta = fs::metadata(path).ok()?;
+    let key = std::path::absolute(path).ok()?.to_str()?.to_owned();
+    Some((key, meta))
+}
+
+#[cfg(unix)]
+fn inode(meta: &fs::Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::ino(meta)
+}
+
+#[cfg(not(unix))]
+fn inode(_meta: &fs::Metadata) -> u64 {
+    0
+}
+
+fn today() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs() / 86_400)
+}
@@ -0,0 +1,120 @@
+use crate::{catalog, format_size};
+use anyhow::{anyhow, Context, Result};
+use console::style;
+use std::path::{Path, PathBuf};
+
+/// Arguments of `copro history`
+#[derive(clap::Args, Clone)]
+pub struct HistoryArgs {
+    /// show what each run did with this file, found by its source or destination path
+    #[arg(long, value_name = "PATH", conflicts_with = "failing")]
+    file: Option<PathBuf>,
+
+    /// list files that failed in at least RUNS runs
+    #[arg(long, value_name = "RUNS", num_args = 0..=1, default_missing_value = "2")]
+    failing: Option<usize>,
+
+    /// how many runs or files to list at most
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    limit: usize,
+
+    /// catalog to read (default: catalog.db in copro's state directory)
+    #[arg(long, value_name = "FILE")]
+    catalog: Option<PathBuf>,
+}
+
+/// Answer questions about past runs from the catalog --catalog writes
+pub fn run(args: &HistoryArgs) -> Result<()> {
+    let path = args
+        .catalog
+        .clone()
+        .or_else(catalog::default_path)
+        .ok_or_else(|| anyhow!("no state directory (HOME is not set)"))?;
+    if !path.exists() {
+        println!("{}{}", icon!("💡 "),
+            tr!("No catalog at {path} yet; runs are recorded with --catalog", path = style(path.display()).white())
+        );
+        return Ok(());
+    }
+    let context = || format!("could not read the catalog {}", path.display());
+
+    if let Some(file) = &args.file {
+        let rows = catalog::file_history(&path, file, args.limit).with_context(context)?;
+        show_file(file, &rows);
+    } else if let Some(min_runs) = args.failing {
+        let rows = catalog::failing(&path, min_runs, args.limit).with_context(context)?;
+        show_failing(min_runs, &rows);
+    } else {
+        let rows = catalog::runs(&path, args.limit).with_context(context)?;
+        show_runs(&rows);
+    }
+    Ok(())
+}
+
+fn show_runs(rows: &[catalog::RunRow]) {
+    if rows.is_empty() {
+        println!("{}{}", icon!("💡 "), tr!("No runs recorded yet"));
+        return;
+    }
+    for run in rows {
+        let status = match run.status.as_str() {
+            "completed" => style(run.status.as_str()).green(),
+            "running" => style(run.status.as_str()).yellow(),
+            _ => style(run.status.as_str()).red(),
+        };
+        let mut totals = tr!("{count} files ({size})", count = run.copied, size = format_size(run.bytes as f64));
+        if run.failed > 0 {
+            totals.push_str(", ");
+            totals.push_str(&style(tr!("{count} failed", count = run.failed)).red().to_string());
+        }
+        println!("{} {}  {}  {}  {} → {}",
+            style(format!("#{}", run.id)).cyan().bold(),
+            style(&run.started).dim(),
+            status,
+            totals,
+            style(&run.source).white(),
+            style(&run.destination).white()
+        );
+    }
+}
+
+fn show_file(file: &Path, rows: &[catalog::FileRow]) {
+    if rows.is_empty() {
+        println!("{}{}", icon!("💡 "), tr!("No recorded run touched {path}", path = style(file.display()).white()));
+        return;
+    }
+    for row in rows {
+        let detail = match (&row.hash, &row.error) {
+            (_, Some(error)) => style(error.clone()).red().to_string(),
+            (Some(hash), None) => tr!("{size}, BLAKE3 {hash}", size = format_size(row.size as f64), hash = &hash[..16]),
+            (None, None) => format_size(row.size as f64),
+        };
+        let result = match row.result.as_str() {
+            "copied" => style(row.result.as_str()).green(),
+            "failed" => style(row.result.as_str()).red(),
+            _ => style(row.result.as_str()).yellow(),
+        };
+        println!("{} {}  {}  {} → {} ({})",
+            style(format!("#{}", row.run)).cyan().bold(),
+            style(&row.started).dim(),
+            result,
+            style(&row.source).white(),
+            style(&row.destination).white(),
+            detail
+        );
+    }
+}
+
+fn show_failing(min_runs: usize, rows: &[catalog::FailingRow]) {
+    if rows.is_empty() {
+        println!("{}{}", icon!("🎉 "), tr!("No file failed in {count} or more runs", count = min_runs));
+        return;
+    }
+    for row in rows {
+        println!("{} {} ({})",
+            style(tr!("{count} runs", count = row.runs)).red().bold(),
+            style(&row.source).white(),
+            tr!("last on {date}: {error}", date = &row.last, error = style(&row.error).red())
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use std::{collections::HashMap, env, sync::OnceLock};
+
+/// Catalogs of translated messages, keyed by the English text used in the
+/// code. A message missing from a catalog is shown in English.
+const CATALOGS: [(&str, &str); 1] = [("ja", include_str!("../locales/ja.json"))];
+
+/// The catalog for the user's language, from the first of LC_ALL,
+/// LC_MESSAGES and LANG that is set (as with gettext)
+fn catalog() -> &'static HashMap<String, String> {
+    static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        CATALOGS
+            .iter()
+            .find(|(language, _)| locale.starts_with(language))
+            .and_then(|(_, catalog)| serde_json::from_str(catalog).ok())
+            .unwrap_or_default()
+    })
+}
+
+/// `message` in the user's language
+pub fn text(message: &'static str) -> &'static str {
+    catalog().get(message).map_or(message, String::as_str)
+}
+
+/// Put `args` into the `{name}` placeholders of `template`; unknown
+/// placeholders are left as they are
+pub fn fill(template: &str, args: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after
+            .find('}')
+            .and_then(|end| args.iter().find(|(name, _)| *name == &after[..end]).map(|(_, value)| (end, value)));
+        match value {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,78 @@
+/// Keeps the system from going to sleep until dropped. Best effort: when the
+/// platform mechanism is unavailable the copy simply runs without it.
+pub struct SleepInhibitor {
+    #[cfg(not(windows))]
+    child: Option<std::process::Child>,
+}
+
+impl SleepInhibitor {
+    /// Hold `systemd-inhibit` (Linux) or `caffeinate` (macOS, an IOPMAssertion)
+    /// for as long as the guard lives
+    #[cfg(not(windows))]
+    pub fn acquire() -> Self {
+        use std::process::{Command, Stdio};
+
+        let mut command = if cfg!(target_os = "macos") {
+            // -w also releases the assertion should copro be killed
+            let mut c = Command::new("caffeinate");
+            c.args(["-i", "-w", &std::process::id().to_string()]);
+            c
+        } else {
+            let mut c = Command::new("systemd-inhibit");
+            c.args(["--what=sleep:idle", "--who=copro", "--why=Copying files", "--mode=block", "sleep", "infinity"]);
+            // The guard is not dropped when copro exits through process::exit,
+            // so the kernel ends the inhibitor along with copro instead (strictly,
+            // along with the thread that acquired it, which is the main one)
+            #[cfg(target_os = "linux")]
+            {
+                use std::os::unix::process::CommandExt;
+                let parent = std::process::id() as libc::pid_t;
+                // SAFETY: prctl, getppid and _exit are async-signal-safe, as pre_exec requires
+                unsafe {
+                    c.pre_exec(move || {
+                        if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        // copro may already have gone before the signal was set up
+                        if libc::getppid() != parent {
+                            libc::_exit(0);
+                        }
+                        Ok(())
+                    });
+                }
+            }
+            c
+        };
+        let child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok();
+        Self { child }
+    }
+
+    /// Tell Windows the system is required while this thread is copying
+    #[cfg(windows)]
+    pub fn acquire() -> Self {
+        use windows_sys::Win32::System::Power::{ES_CONTINUOUS, ES_SYSTEM_REQUIRED, SetThreadExecutionState};
+        unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+        Self {}
+    }
+}
+
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        #[cfg(not(windows))]
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        #[cfg(windows)]
+        unsafe {
+            windows_sys::Win32::System::Power::SetThreadExecutionState(
+                windows_sys::Win32::System::Power::ES_CONTINUOUS,
+            )
+        };
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{datepath::Now, preserve::{self, Preserve}, CopyArgs, SymlinkKind};
+use serde::Deserialize;
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+
+/// One line of `--jobs-from`, e.g.
+/// `{"src": "/data/a", "dest": "/backup/a", "options": {"verify": true}}`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Line {
+    src: PathBuf,
+    dest: PathBuf,
+    #[serde(default)]
+    options: Options,
+}
+
+/// Options a job may set for itself; the rest come from the command line
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct Options {
+    fast_mode: Option<bool>,
+    link: Option<bool>,
+    symlink: Option<SymlinkKind>,
+    verify: Option<bool>,
+    preserve: Option<Vec<Preserve>>,
+    no_preserve: Option<Vec<Preserve>>,
+}
+
+/// A copy job: everything under `source` goes to `destination`, with its own options
+pub struct Job {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub cli: CopyArgs,
+}
+
+/// Read jobs as JSON lines from `path` (`-` for stdin); blank lines are ignored
+pub fn load(path: &Path, cli: &CopyArgs) -> io::Result<Vec<Job>> {
+    let reader: Box<dyn BufRead> = if path == Path::new("-") {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(io::BufReader::new(fs::File::open(path)?))
+    };
+
+    let now = Now::local();
+    let mut jobs = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed: Line = serde_json::from_str(&line).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("job on line {}: {}", number + 1, e))
+        })?;
+
+        let mut job_cli = cli.clone();
+        let options = parsed.options;
+        if let Some(fast_mode) = options.fast_mode {
+            job_cli.fast_mode = fast_mode;
+        }
+        if let Some(link) = options.link {
+            job_cli.link = link;
+        }
+        if options.symlink.is_some() {
+            job_cli.symlink = options.symlink;
+        }
+        if let Some(verify) = options.verify {
+            job_cli.verify = verify;
+        }
+        if options.preserve.is_some() || options.no_preserve.is_some() {
+            let preserve = options.preserve.unwrap_or_else(|| cli.preserve.clone());
+            let no_preserve = options.no_preserve.unwrap_or_else(|| cli.no_preserve.clone());
+            job_cli.preserve = preserve::resolve(&preserve, &no_preserve);
+        }
+        jobs.push(Job { source: parsed.src, destination: now.expand(&parsed.dest), cli: job_cli });
+    }
+    Ok(jobs)
+}
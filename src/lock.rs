@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Name of the lock file a run keeps in its destination
+pub const NAME: &str = ".copro.lock";
+
+/// Lock files this process holds, so they can be removed on the way out of
+/// an exit that skips Drop
+static HELD: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// An advisory lock on a destination directory: a `.copro.lock` file,
+/// flock()ed on Unix, held while a run writes there so a second copro
+/// syncing into the same place does not interleave its temporary files and
+/// renames with this one's. The file holds the process id of the run and is
+/// removed when the lock is dropped; one left behind by a killed run holds
+/// no lock and is simply taken over.
+pub struct DestinationLock {
+    path: PathBuf,
+    _file: fs::File,
+}
+
+/// Why a destination could not be locked
+pub enum LockError {
+    /// Another run holds it; its process id, once it has written it
+    Busy(Option<u32>),
+    Io(io::Error),
+}
+
+impl DestinationLock {
+    pub fn acquire(dir: &Path) -> Result<Self, LockError> {
+        fs::create_dir_all(dir).map_err(LockError::Io)?;
+        let path = dir.join(NAME);
+        loop {
+            let mut file = fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .map_err(LockError::Io)?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(fs::TryLockError::WouldBlock) => {
+                    let mut pid = String::new();
+                    let _ = file.read_to_string(&mut pid);
+                    return Err(LockError::Busy(pid.trim().parse().ok()));
+                }
+                Err(fs::TryLockError::Error(e)) => return Err(LockError::Io(e)),
+            }
+            // The run that held it may have removed the file between our open
+            // and lock; the one to lock is whatever is there now
+            if !is_at(&file, &path) {
+                continue;
+            }
+            file.set_len(0).map_err(LockError::Io)?;
+            write!(file, "{}", std::process::id()).map_err(LockError::Io)?;
+            if let Ok(mut held) = HELD.lock() {
+                held.push(path.clone());
+            }
+            return Ok(Self { path, _file: file });
+        }
+    }
+}
+
+impl Drop for DestinationLock {
+    fn drop(&mut self) {
+        if let Ok(mut held) = HELD.lock() {
+            held.retain(|path| *path != self.path);
+        }
+        // Removed while still locked, so no other run can take the old file
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Remove every lock file this process holds, before a `process::exit`,
+/// which drops nothing. The locks themselves go with the process.
+pub fn release_all() {
+    if let Ok(mut held) = HELD.lock() {
+        for path in held.drain(..) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Whether `file` is still the file at `path`
+fn is_at(file: &fs::File, path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (file.metadata(), fs::metadata(path)) {
+            (Ok(open), Ok(named)) => open.dev() == named.dev() && open.ino() == named.ino(),
+            _ => false,
+        }
+    }
+    // std has no stable file identity on Windows, so there the file is taken
+    // to be the one at `path` without checking
+    #[cfg(not(unix))]
+    {
+        let _ = (file, path);
+        true
+    }
+}
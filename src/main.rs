@@ -1,23 +1,125 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use console::{style, Term};
-use dialoguer::Input;
+#[cfg(feature = "prompts")]
+use dialoguer::Confirm;
 use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
     fs,
-    path::{Path, PathBuf},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
     thread,
-    time::{Duration, Instant},
-    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
+    time::{Duration, Instant, SystemTime},
+    sync::{Arc, Mutex, OnceLock, atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}},
     sync::mpsc,
 };
 use anyhow::Result;
 use signal_hook::{consts::SIGINT, iterator::Signals};
 
+/// `emoji` followed by `text`, or just `text` in builds without the `emoji`
+/// feature and in --accessible mode
+#[cfg(feature = "emoji")]
+macro_rules! icon {
+    ($emoji:literal) => { if crate::accessible() { "" } else { $emoji } };
+    ($emoji:literal, $text:literal) => {
+        if crate::accessible() { String::from(tr!($text)) } else { format!("{}{}", $emoji, tr!($text)) }
+    };
+}
+#[cfg(not(feature = "emoji"))]
+macro_rules! icon {
+    ($emoji:literal) => { "" };
+    ($emoji:literal, $text:literal) => { String::from(tr!($text)) };
+}
+
+/// A message in the user's language (see `i18n`): `tr!("text")`, or
+/// `tr!("{count} files", count = n)` to fill in placeholders
+macro_rules! tr {
+    ($message:literal) => { crate::i18n::text($message) };
+    ($message:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        crate::i18n::fill(crate::i18n::text($message), &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+mod ads;
+mod bench;
+mod bell;
+mod blockdev;
+mod catalog;
+mod check;
+mod checkpoint;
+mod clean;
+mod compress;
+mod crypto;
+mod datepath;
+mod fanout;
+mod hashcache;
+mod history;
+mod i18n;
+mod inhibit;
+mod jobs;
+mod lock;
+mod man;
+mod manifest;
+mod media;
+mod merge;
+mod noatime;
+mod ownership;
+#[cfg(feature = "prompts")]
+mod picker;
+mod pipe;
+mod preserve;
+mod print0;
+mod recent;
+mod report;
+mod restore;
+#[cfg(feature = "prompts")]
+mod select;
+mod serve;
+mod state;
+mod status;
+mod storage;
+mod syncstate;
+mod templates;
+mod throughput;
+mod taskbar;
+mod undo;
+mod versions;
+mod verify;
+mod vss;
+mod walk;
+mod watchdog;
+
+use checkpoint::Checkpoint;
+use pipe::HashingReader;
+use compress::Codec;
+use crypto::Crypto;
+use manifest::{Manifest, ManifestEntry};
+use merge::{MergePolicy, Sources};
+use preserve::Preserve;
+use walk::BrokenLinks;
+
 /// File copy tool with dynamic terminal animation
-#[derive(Parser)]
+#[derive(Parser, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// source path
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// copro SOURCE DESTINATION on its own is copro cp
+    #[command(flatten)]
+    copy: CopyArgs,
+}
+
+/// Options of a copy, shared by copro cp, mv and sync
+#[derive(clap::Args, Clone)]
+struct CopyArgs {
+    /// remove each source file once it has been copied (and verified, with --verify): copro mv
+    #[arg(skip)]
+    remove_sources: bool,
+
+    /// source path (repeat to merge several directories into the destination)
     #[arg(short, long)]
-    source: Option<PathBuf>,
+    source: Vec<PathBuf>,
 
     /// target path
     #[arg(short, long)]
@@ -29,9 +131,10 @@ struct Cli {
     /// destination path as positional argument
     destination_positional: Option<PathBuf>,
 
-    /// show per-file copy success messages
-    #[arg(short, long)]
-    verbose: bool,
+    /// per-file messages: -v lists copied files, -vv adds sizes, durations and skip reasons,
+    /// -vvv explains how each file is written (new, overwritten, renamed, ...)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
 
     /// skip temporary file protection for maximum speed
     #[arg(long)]
@@ -40,26 +143,438 @@ struct Cli {
     /// reduce animation update frequency for better performance
     #[arg(long)]
     low_animation: bool,
+
+    /// pause this many milliseconds after each file, so the animation can be watched (for demos)
+    #[arg(long, value_name = "MS")]
+    demo_delay: Option<u64>,
+
+    /// group the final summary by file extension (count and bytes)
+    #[arg(long)]
+    by_extension: bool,
+
+    /// show progress and speed in the terminal window/tab title
+    #[arg(long)]
+    title: bool,
+
+    /// report progress to the terminal (OSC 9;4) for a native taskbar/tab indicator
+    #[arg(long)]
+    osc_progress: bool,
+
+    /// show the progress and per-file messages in the terminal's alternate screen, leaving only
+    /// the final summary in the scrollback
+    #[arg(long)]
+    altscreen: bool,
+
+    /// show progress on the taskbar button (Windows), and to the terminal as with --osc-progress
+    #[arg(long)]
+    taskbar: bool,
+
+    /// answer HTTP requests on this address (e.g. 127.0.0.1:7700) with the live state of the run as JSON
+    #[arg(long, value_name = "ADDR")]
+    serve_progress: Option<std::net::SocketAddr>,
+
+    /// plain output for screen readers and braille displays: no animation, emoji or colors, progress as periodic sentences
+    #[arg(long)]
+    accessible: bool,
+
+    /// show sizes in decimal units (kB, MB, GB) instead of binary ones (KiB, MiB, GiB)
+    #[arg(long)]
+    si: bool,
+
+    /// ring the terminal bell when the run finishes or fails
+    #[arg(long)]
+    bell: bool,
+
+    /// stop gracefully once this much wall-clock time has passed (e.g. 90s, 45m, 2h, 1h30m)
+    #[arg(long, value_parser = parse_duration)]
+    timeout: Option<Duration>,
+
+    /// make Ctrl+C ask before stopping; no answer within the given time (default 30s) carries on
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, num_args = 0..=1, require_equals = true, default_missing_value = "30s")]
+    confirm_stop: Option<Duration>,
+
+    /// warn in the progress line once no data has moved for this long (e.g. 30s, 5m)
+    #[arg(long, value_parser = parse_duration, default_value = "60s")]
+    stall_after: Duration,
+
+    /// give up on a file stalled for --stall-after; it is failed and retried in the retry passes
+    #[arg(long)]
+    abandon_stalled: bool,
+
+    /// give up on any single file whose copy takes longer than this (e.g. 5m); it is recorded as failed
+    #[arg(long, value_parser = parse_duration)]
+    file_timeout: Option<Duration>,
+
+    /// stop the run once more than this many files have failed
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// stop the whole run at the first file that fails, without retrying it; exits with status 1
+    #[arg(long, conflicts_with = "max_errors")]
+    fail_fast: bool,
+
+    /// how many times to re-attempt failed files after the main pass
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    retry_passes: usize,
+
+    /// copy files that changed while they were being copied again, up to this many times, once the main pass is done
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    settle: usize,
+
+    /// pick what to copy from the scanned tree (checkboxes, collapsible directories) before starting
+    #[arg(long, conflicts_with = "jobs_from")]
+    select: bool,
+
+    /// print the resolved source → destination pairs without copying anything
+    #[arg(long)]
+    list_only: bool,
+
+    /// run copy jobs read as JSON lines from this file or `-` for stdin: {"src", "dest", "options"}
+    #[arg(long, value_name = "FILE", conflicts_with_all = [
+        "source", "destination", "source_positional", "destination_positional", "list_only",
+        "encrypt", "decrypt", "decompress", "also_to", "offset", "length", "resume", "vss",
+    ])]
+    jobs_from: Option<PathBuf>,
+
+    /// write the destination of every copied file to stdout, NUL separated (other output goes to stderr)
+    #[arg(long)]
+    print0: bool,
+
+    /// re-read every copy after the run and compare it with the source (BLAKE3)
+    #[arg(long, conflicts_with_all = ["encrypt", "decrypt", "decompress", "link", "symlink"])]
+    verify: bool,
+
+    /// verify only a random share of the copies (e.g. 5%), plus every file above --verify-above
+    #[arg(long, value_name = "PERCENT", value_parser = parse_percent,
+          conflicts_with_all = ["verify", "encrypt", "decrypt", "decompress", "link", "symlink"])]
+    verify_sample: Option<f64>,
+
+    /// with --verify-sample, always verify files at least this large
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, default_value = "1G", requires = "verify_sample")]
+    verify_above: u64,
+
+    /// how many copies to hash at once while verifying (default: 1 on spinning disks, up to 4 on
+    /// SATA SSDs, one per CPU on NVMe and other storage)
+    #[arg(long, value_name = "N")]
+    verify_threads: Option<usize>,
+
+    /// hash every source again instead of reusing hashes that earlier runs took of unchanged files
+    #[arg(long)]
+    no_hash_cache: bool,
+
+    /// before overwriting a file, keep up to N previous versions of it as NAME.~1~ (newest) to NAME.~N~
+    #[arg(long, value_name = "N")]
+    backups: Option<usize>,
+
+    /// hard-link files that have not changed since the snapshot in DIR (same size and not older, or
+    /// same content with --checksum) instead of copying them again
+    #[arg(long, value_name = "DIR",
+          conflicts_with_all = ["link", "symlink", "encrypt", "decrypt", "decompress", "offset", "length", "resume", "chmod", "chown"])]
+    link_dest: Option<PathBuf>,
+
+    /// skip files whose copy is already up to date: same size and not older than the source
+    #[arg(long, conflicts_with_all = ["link", "symlink", "encrypt", "decrypt", "decompress"])]
+    update: bool,
+
+    /// with --update, remember in this file what each sync left in the destination, so the
+    /// next run skips unchanged sources without looking at the destination
+    #[arg(long, value_name = "FILE")]
+    sync_state: Option<PathBuf>,
+
+    /// with --update, compare contents (BLAKE3) instead of trusting size and modification
+    /// time, for destinations with unreliable timestamps
+    #[arg(long)]
+    checksum: bool,
+
+    /// write an audit manifest (JSON) of every file copied, with sizes and hashes
+    #[arg(long, value_name = "FILE")]
+    manifest: Option<PathBuf>,
+
+    /// record the run and what became of every file, with sizes and hashes, in a SQLite catalog
+    /// (--catalog=FILE; by default catalog.db in copro's state directory)
+    #[arg(long, value_name = "FILE", num_args = 0..=1, require_equals = true)]
+    catalog: Option<Option<PathBuf>>,
+
+    /// write a CSV row for every file: source, destination, bytes, seconds, status and error
+    #[arg(long, value_name = "FILE")]
+    report_csv: Option<PathBuf>,
+
+    /// write a self-contained HTML page with the run's totals, a chart of its throughput and a
+    /// sortable table of the failed files
+    #[arg(long, value_name = "FILE")]
+    report_html: Option<PathBuf>,
+
+    /// when merging several sources, which version of a shared path to copy
+    #[arg(long, value_enum, value_name = "POLICY", default_value = "newest")]
+    merge: MergePolicy,
+
+    /// skip the pre-flight confirmation prompt
+    #[arg(short, long)]
+    yes: bool,
+
+    /// run even if the destination lies inside the source tree
+    #[arg(long)]
+    force: bool,
+
+    /// write files even where a symlink or odd name leads outside the destination tree
+    #[arg(long)]
+    allow_outside: bool,
+
+    /// create hardlinks instead of copying (source and destination must share a filesystem)
+    #[arg(long)]
+    link: bool,
+
+    /// create symlinks pointing back at the source files instead of copying
+    #[arg(long, value_enum, num_args = 0..=1, require_equals = true,
+          default_missing_value = "absolute", conflicts_with = "link")]
+    symlink: Option<SymlinkKind>,
+
+    /// start copying a single source file at this byte offset (e.g. 4096, 512K, 2G)
+    #[arg(long, value_parser = parse_size, conflicts_with_all = ["link", "symlink"])]
+    offset: Option<u64>,
+
+    /// copy at most this many bytes of a single source file (e.g. 100M)
+    #[arg(long, value_parser = parse_size, conflicts_with_all = ["link", "symlink"])]
+    length: Option<u64>,
+
+    /// when copying a block device, or an image to one, write at most this many bytes per
+    /// second (e.g. 20M)
+    #[arg(long, value_name = "RATE", value_parser = parse_size)]
+    bwlimit: Option<u64>,
+
+    /// continue an interrupted single-file copy if the partial data matches the source
+    #[arg(long, conflicts_with_all = ["link", "symlink"])]
+    resume: bool,
+
+    /// encrypt every file with age for this recipient (age1...), writing .age files
+    #[arg(long, value_name = "RECIPIENT",
+          conflicts_with_all = ["link", "symlink", "offset", "length", "resume"])]
+    encrypt: Vec<String>,
+
+    /// decrypt .age files with the identities in this file, dropping the extension
+    #[arg(long, value_name = "IDENTITY_FILE",
+          conflicts_with_all = ["encrypt", "link", "symlink", "offset", "length", "resume"])]
+    decrypt: Option<PathBuf>,
+
+    /// decompress .gz/.zst/.xz source files on the fly, dropping the extension
+    #[arg(long, conflicts_with_all = ["encrypt", "decrypt", "link", "symlink", "offset", "length", "resume"])]
+    decompress: bool,
+
+    /// also write every file to this directory, sharing one read of the source (repeatable)
+    #[arg(long, value_name = "DIR",
+          conflicts_with_all = ["encrypt", "decrypt", "decompress", "link", "symlink", "offset", "length", "resume"])]
+    also_to: Vec<PathBuf>,
+
+    /// copy from a Volume Shadow Copy of the source volume so open files are consistent (Windows, needs Administrator)
+    #[arg(long, conflicts_with_all = ["link", "symlink"])]
+    vss: bool,
+
+    /// also copy NTFS alternate data streams such as Zone.Identifier (Windows)
+    #[arg(long, conflicts_with_all = ["link", "symlink"])]
+    ads: bool,
+
+    /// carry these attributes over to the copies (comma separated: acl, finder, owner, atime, or all)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "ATTRS", conflicts_with_all = ["link", "symlink"])]
+    preserve: Vec<Preserve>,
+
+    /// leave these attributes out of --preserve, e.g. --preserve=all --no-preserve=owner
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "ATTRS")]
+    no_preserve: Vec<Preserve>,
+
+    /// with --preserve=owner, give copies these user IDs in place of the source's: old:new pairs (names, IDs or * for any) or a file of them
+    #[arg(long, value_name = "MAP", value_parser = ownership::parse_user_map)]
+    usermap: Option<ownership::IdMap>,
+
+    /// with --preserve=owner, give copies these group IDs in place of the source's: old:new pairs or a file of them
+    #[arg(long, value_name = "MAP", value_parser = ownership::parse_group_map)]
+    groupmap: Option<ownership::IdMap>,
+
+    /// with --preserve=owner, record the owner and mode in an extended attribute (user.copro.stat) instead of setting them, so an unprivileged copy keeps them for a later restore
+    #[arg(long)]
+    fake_super: bool,
+
+    /// set this mode on every file written, in octal; a D prefix sets it on the directories created (e.g. 644, D755,F644)
+    #[arg(long, value_name = "MODE", value_parser = ownership::parse_modes, conflicts_with_all = ["link", "symlink"])]
+    chmod: Option<ownership::Modes>,
+
+    /// make everything written belong to this user and/or group (user:group, user or :group; names or numeric IDs)
+    #[arg(long, value_name = "USER:GROUP", value_parser = ownership::parse_owner, conflicts_with_all = ["link", "symlink"])]
+    chown: Option<ownership::Owner>,
+
+    /// give every file written a modification time of now, so build systems and file watchers see the copies as new
+    #[arg(long, conflicts_with_all = ["link", "symlink"])]
+    touch: bool,
+
+    /// what to do with symlinks whose target is missing
+    #[arg(long, value_enum, value_name = "POLICY", default_value = "skip")]
+    broken_symlinks: BrokenLinks,
+
+    /// do not record this run for `copro undo`. Overwritten files are otherwise kept as hard links in the
+    /// state directory, which only works on its filesystem; elsewhere they are not kept (see --undo-copies)
+    #[arg(long)]
+    no_undo: bool,
+
+    /// for `copro undo`, copy overwritten files that cannot be hard-linked (up to 256 MiB each, 1 GiB per run)
+    #[arg(long, conflicts_with = "no_undo")]
+    undo_copies: bool,
+
+    /// let the system sleep during the copy (by default copro keeps it awake)
+    #[arg(long)]
+    allow_sleep: bool,
+
+    /// once the run is over, flush the destination's filesystem to its device (syncfs)
+    #[arg(long)]
+    sync_fs: bool,
+
+    /// once the run has finished, flush the destination and unmount and power off the drive
+    /// it is on, so a USB stick or SD card can be pulled out
+    #[arg(long)]
+    eject: bool,
+
+    /// with --resume, checkpoint large files every this many bytes in a .part file
+    #[arg(long, value_parser = parse_size, default_value = "64M", requires = "resume")]
+    chunk_size: u64,
+}
+
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// copy files and directories (what copro SOURCE DESTINATION does)
+    Cp(Box<CopyArgs>),
+    /// copy, then remove each source file that was copied; source directories this empties go too
+    Mv(Box<CopyArgs>),
+    /// bring a destination up to date, copying only new and changed files (cp --update)
+    Sync(Box<CopyArgs>),
+    /// compare a destination with its source by content, without copying anything
+    Verify(verify::VerifyArgs),
+    /// remove the temporary and .part files an interrupted copy left in a destination
+    Clean(clean::CleanArgs),
+    /// verify a destination against a manifest written with --manifest
+    Check(check::CheckArgs),
+    /// measure copy throughput on a target with a generated workload
+    Bench(bench::BenchArgs),
+    /// roll back the most recent run: remove what it created, restore what it overwrote
+    Undo(undo::UndoArgs),
+    /// copy a backup back to a target, decrypting .age files and checking it against its manifest
+    Restore(restore::RestoreArgs),
+    /// list past runs recorded with --catalog, what they did with a file, or files that keep failing
+    History(history::HistoryArgs),
+    /// print a man page generated from these options, or write one per command for packaging
+    Man(man::ManArgs),
+}
+
+#[derive(Clone, Copy, ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SymlinkKind {
+    /// link targets are absolute paths
+    Absolute,
+    /// link targets are relative to the link's directory
+    Relative,
+}
+
+/// Why a run stopped before copying every file
+#[derive(Clone, Copy)]
+enum StopReason {
+    Interrupted,
+    Timeout,
+    MaxErrors(usize),
+    FailFast,
+}
+
+/// Longest pause between frames while no progress is being made
+const MAX_IDLE_INTERVAL: Duration = Duration::from_millis(800);
+
+/// Files smaller than this are left out of the slow-file report
+const SLOW_FILE_MIN_BYTES: u64 = 1_000_000;
+/// Files needed for a meaningful median rate
+const SLOW_FILE_MIN_SAMPLES: usize = 5;
+/// How many times below the median rate a file has to be to count as slow
+const SLOW_FILE_FACTOR: f64 = 5.0;
+
+/// How often --accessible reports progress
+const ACCESSIBLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Set once by --accessible: no animation, emoji or colors, just sentences
+static ACCESSIBLE: AtomicBool = AtomicBool::new(false);
+
+fn accessible() -> bool {
+    ACCESSIBLE.load(Ordering::Relaxed)
+}
+
+/// Set once by --si: sizes in powers of 1000 rather than 1024
+static SI_UNITS: AtomicBool = AtomicBool::new(false);
+
+/// Temporary files of the copy in progress, removed should a second Ctrl+C
+/// end the run without waiting for it
+static IN_FLIGHT: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Set while --confirm-stop asks whether to stop, so the progress displays
+/// hold still instead of drawing over the question
+static STOP_PROMPT: AtomicBool = AtomicBool::new(false);
+
+fn stop_prompt_open() -> bool {
+    STOP_PROMPT.load(Ordering::Relaxed)
 }
 
+/// Extra attempts, and the pause before each, for files another program has locked
+const IN_USE_RETRIES: u32 = 3;
+const IN_USE_DELAY: Duration = Duration::from_millis(500);
+
 struct AnimatedProgress {
     term: Term,
-    current: Arc<Mutex<usize>>,
-    total: usize,
+    /// Held while a frame or a message is written, so the two never interleave
+    drawing: Arc<Mutex<()>>,
+    current: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+    bytes: Arc<AtomicU64>,
+    total_bytes: Arc<AtomicU64>,
+    scanning: Arc<AtomicBool>,
     start_time: Instant,
     animation_chars: Vec<&'static str>,
     wave_chars: Vec<&'static str>,
     colors: Vec<console::Color>,
     should_stop: Arc<AtomicBool>,
     animation_handle: Option<thread::JoinHandle<()>>,
+    terminal_title: bool,
+    osc_progress: bool,
+    /// Run the display in the alternate screen (--altscreen)
+    altscreen: bool,
+    taskbar_progress: bool,
+    bell: bool,
+    failures: Arc<AtomicUsize>,
+    /// The latest failed file, for --serve-progress
+    last_failure: Arc<Mutex<Option<status::Failure>>>,
+    current_file: Arc<Mutex<Option<PathBuf>>>,
+    /// Smoothed copy speed, shared by the progress line and the status line
+    throughput: Arc<Mutex<throughput::Throughput>>,
+    stall: Option<Arc<watchdog::Stall>>,
+    templates: templates::Templates,
+    tally: Tally,
+}
+
+/// What became of the files of a run, beyond the failures
+#[derive(Default)]
+struct Tally {
+    copied: usize,
+    /// Left out by --select or a --merge policy, or skipped during the copy
+    skipped: usize,
+    /// Already up to date in the destination (--update, links from an earlier run)
+    unchanged: usize,
+    /// Copied under another name by --merge keep-both
+    renamed: usize,
+    /// Copied while the source changed, so the copy may be inconsistent
+    unstable: usize,
 }
 
 impl AnimatedProgress {
-    fn new(total: usize) -> Self {
+    fn new(total: usize, total_bytes: u64) -> Self {
         Self {
             term: Term::stdout(),
-            current: Arc::new(Mutex::new(0)),
-            total,
+            drawing: Arc::default(),
+            current: Arc::new(AtomicUsize::new(0)),
+            total: Arc::new(AtomicUsize::new(total)),
+            bytes: Arc::new(AtomicU64::new(0)),
+            total_bytes: Arc::new(AtomicU64::new(total_bytes)),
+            scanning: Arc::new(AtomicBool::new(false)),
             start_time: Instant::now(),
             animation_chars: vec!["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
             wave_chars: vec!["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"],
@@ -73,41 +588,118 @@ impl AnimatedProgress {
             ],
             should_stop: Arc::new(AtomicBool::new(false)),
             animation_handle: None,
+            terminal_title: false,
+            osc_progress: false,
+            altscreen: false,
+            taskbar_progress: false,
+            bell: false,
+            failures: Arc::new(AtomicUsize::new(0)),
+            last_failure: Arc::default(),
+            current_file: Arc::new(Mutex::new(None)),
+            throughput: Arc::default(),
+            stall: None,
+            templates: templates::Templates::default(),
+            tally: Tally::default(),
         }
     }
 
     fn start_animation(&mut self, low_animation: bool) {
         let current = Arc::clone(&self.current);
-        let total = self.total;
+        let total = Arc::clone(&self.total);
+        let bytes = Arc::clone(&self.bytes);
+        let total_bytes = Arc::clone(&self.total_bytes);
+        let scanning = Arc::clone(&self.scanning);
         let term = self.term.clone();
+        let drawing = Arc::clone(&self.drawing);
         let animation_chars = self.animation_chars.clone();
         let wave_chars = self.wave_chars.clone();
         let colors = self.colors.clone();
         let start_time = self.start_time;
         let should_stop = Arc::clone(&self.should_stop);
+        let terminal_title = self.terminal_title;
+        let osc_progress = self.osc_progress;
+        let taskbar_progress = self.taskbar_progress;
+        let failures = Arc::clone(&self.failures);
+        let current_file = Arc::clone(&self.current_file);
+        let throughput = Arc::clone(&self.throughput);
+        let stall = self.stall.clone();
+
+        if terminal_title {
+            // Save the user's title so it can be restored afterwards
+            let _ = term.write_str("\x1b[22;0t");
+        }
+        // Only a terminal has one, and screen readers follow the main screen
+        self.altscreen &= term.is_term() && !accessible();
+        if self.altscreen {
+            let _ = term.write_str("\x1b[?1049h\x1b[H");
+        }
 
         let handle = thread::spawn(move || {
+            let mut last_title = String::new();
+            let mut last_osc = String::new();
+            // Created here because the taskbar's COM object belongs to this thread
+            let taskbar = taskbar_progress.then(taskbar::Taskbar::attach).flatten();
+            let mut last_taskbar = None;
+            let base_interval = Duration::from_millis(if low_animation { 200 } else { 100 });
+            let mut interval = base_interval;
+            let mut last_seen = None;
             let mut frame = 0;
+            let mut last_sentence = Instant::now();
+            let mut was_stalled = false;
             while !should_stop.load(Ordering::Relaxed) {
-                let current_count = *current.lock().unwrap();
-                if current_count >= total {
+                let current_count = current.load(Ordering::Relaxed);
+                let still_scanning = scanning.load(Ordering::Relaxed);
+                let total = total.load(Ordering::Relaxed);
+                let total_bytes = total_bytes.load(Ordering::Relaxed);
+                if current_count >= total && !still_scanning {
                     break;
                 }
+                if stop_prompt_open() {
+                    thread::park_timeout(base_interval);
+                    continue;
+                }
 
                 let elapsed = start_time.elapsed().as_secs_f32();
                 let spinner = animation_chars[frame % animation_chars.len()];
-                
-                // Create dynamic wave effect
-                let mut wave_bar = String::new();
-                for i in 0..20 {
-                    let wave_offset = (elapsed * 3.0 + i as f32 * 0.3).sin();
-                    let wave_index = ((wave_offset + 1.0) * 3.5) as usize % wave_chars.len();
-                    let color_index = (frame / 2 + i) % colors.len();
-                    wave_bar.push_str(&format!("{}", style(wave_chars[wave_index]).fg(colors[color_index])));
+
+                // Progress percentage with rainbow effect (by bytes when sizes are known)
+                let bytes_done = bytes.load(Ordering::Relaxed).min(total_bytes);
+                let progress = if total_bytes > 0 {
+                    (bytes_done as f64 / total_bytes as f64 * 100.0) as u8
+                } else if total > 0 {
+                    (current_count as f32 / total as f32 * 100.0) as u8
+                } else {
+                    0
+                };
+                let (rate, left) = match throughput.lock() {
+                    Ok(mut throughput) => (throughput.sample(bytes_done), throughput.eta(total_bytes - bytes_done)),
+                    Err(_) => (None, None),
+                };
+                // A hung copy says so instead of animating as if all was well
+                let stalled = stall.as_ref().and_then(|stall| stall.stalled_for()).map(|stalled| {
+                    let file = current_file.lock().ok().and_then(|f| f.clone()).unwrap_or_default();
+                    let name = file.file_name().unwrap_or(file.as_os_str()).to_string_lossy().into_owned();
+                    tr!("stalled on {file} for {seconds}s", file = name, seconds = stalled.as_secs())
+                });
+                if accessible() {
+                    // A whole sentence now and then instead of a line redrawn in place
+                    if last_sentence.elapsed() >= ACCESSIBLE_INTERVAL {
+                        let sentence = if still_scanning {
+                            tr!("Copied {done} of {total} files found so far, {percent} percent.",
+                                done = current_count, total = total, percent = progress.min(100))
+                        } else {
+                            tr!("Copied {done} of {total} files, {percent} percent.",
+                                done = current_count, total = total, percent = progress.min(100))
+                        };
+                        let warning = stalled.as_ref().map(|s| tr!(" Warning: {stall}.", stall = s)).unwrap_or_default();
+                        let _drawing = drawing.lock();
+                        let _ = term.write_line(&format!("{}{}", sentence, warning));
+                        last_sentence = Instant::now();
+                    }
+                    thread::park_timeout(base_interval);
+                    continue;
                 }
 
-                // Progress percentage with rainbow effect
-                let progress = if total > 0 { (current_count as f32 / total as f32 * 100.0) as u8 } else { 0 };
                 let progress_color = match progress {
                     0..=20 => console::Color::Red,
                     21..=40 => console::Color::Yellow,
@@ -119,35 +711,121 @@ impl AnimatedProgress {
 
                 // Create pulsing effect for file counter
                 let pulse_intensity = (elapsed * 4.0).sin().abs();
+                // "+" while the walker is still finding files
+                let file_counter = format!("{}/{}{}", current_count, total, if still_scanning { "+" } else { "" });
                 let file_counter_style = if pulse_intensity > 0.7 {
-                    style(format!("{}/{}", current_count, total)).bold().fg(console::Color::White)
+                    style(file_counter).bold().fg(console::Color::White)
                 } else {
-                    style(format!("{}/{}", current_count, total)).fg(console::Color::Cyan)
+                    style(file_counter).fg(console::Color::Cyan)
                 };
 
-                // Animated brackets
-                let bracket_char = if (frame / 5) % 2 == 0 { "◤" } else { "◢" };
-                let bracket_style = style(bracket_char).fg(colors[frame % colors.len()]);
-
-                // Build the complete animation line
-                let animation_line = format!(
-                    "\r{} {} {} {} {}% {} Copying files... {} {}",
-                    bracket_style,
-                    style(spinner).fg(console::Color::Green).bold(),
-                    wave_bar,
-                    bracket_style,
-                    style(progress).fg(progress_color).bold(),
-                    file_counter_style,
-                    style("✨").fg(console::Color::Yellow),
-                    if frame % 20 < 10 { "🚀" } else { "⚡" }
-                );
+                // The name of the file being copied, in what room the line leaves
+                let columns = term.size().1 as usize;
+                let name = current_file
+                    .lock()
+                    .ok()
+                    .and_then(|f| f.as_ref().and_then(|f| f.file_name()).map(|name| name.to_string_lossy().into_owned()));
+                let activity = match (&stalled, &name) {
+                    (Some(stalled), _) => format!("{}{}", icon!("⏸️  "), style(stalled).yellow().bold()),
+                    (None, Some(name)) => tr!("Copying {file}", file = style(shorten_middle(name, columns.saturating_sub(60).max(12))).white()),
+                    (None, None) => String::from(tr!("Copying files...")),
+                };
+                let animation_line = if cfg!(feature = "animation") {
+                    // Create dynamic wave effect
+                    let mut wave_bar = String::new();
+                    for i in 0..20 {
+                        let wave_offset = (elapsed * 3.0 + i as f32 * 0.3).sin();
+                        let wave_index = ((wave_offset + 1.0) * 3.5) as usize % wave_chars.len();
+                        let color_index = (frame / 2 + i) % colors.len();
+                        wave_bar.push_str(&format!("{}", style(wave_chars[wave_index]).fg(colors[color_index])));
+                    }
+
+                    // Animated brackets
+                    let bracket_char = if (frame / 5) % 2 == 0 { "◤" } else { "◢" };
+                    let bracket_style = style(bracket_char).fg(colors[frame % colors.len()]);
+
+                    // Build the complete animation line
+                    format!(
+                        "\r{} {} {} {} {}% {} {} {}{}",
+                        bracket_style,
+                        style(spinner).fg(console::Color::Green).bold(),
+                        wave_bar,
+                        bracket_style,
+                        style(progress).fg(progress_color).bold(),
+                        file_counter_style,
+                        activity,
+                        style(icon!("✨ ")).fg(console::Color::Yellow),
+                        [icon!("🚀"), icon!("⚡")][frame / 10 % 2]
+                    )
+                } else {
+                    // Plain one-line status for minimal builds
+                    let speed = rate.map(|rate| match left.filter(|_| !still_scanning) {
+                        Some(left) => format!(", {}", tr!("{rate}, {time} left", rate = format_rate(rate), time = throughput::format_eta(left))),
+                        None => format!(", {}", format_rate(rate)),
+                    });
+                    let detail = match (&stalled, &name) {
+                        (Some(stalled), _) => format!(", {}", style(stalled).yellow()),
+                        (None, Some(name)) => format!(", {}", shorten_middle(name, columns.saturating_sub(45).max(12))),
+                        (None, None) => String::new(),
+                    };
+                    format!("\r{}% {}{}{}", progress,
+                        tr!("{count} files", count = file_counter_style),
+                        speed.unwrap_or_default(),
+                        detail
+                    )
+                };
 
+                let draw_start = Instant::now();
+                let frame_guard = drawing.lock();
+                if terminal_title {
+                    let title = match rate {
+                        Some(rate) => format!("copro {}% – {}", progress, format_rate(rate)),
+                        None => format!("copro {}%", progress),
+                    };
+                    if title != last_title {
+                        let _ = term.write_str(&format!("\x1b]0;{}\x07", title));
+                        last_title = title;
+                    }
+                }
+                if osc_progress {
+                    // State 1 is normal progress, 2 turns the indicator red after failures
+                    let state = if failures.load(Ordering::Relaxed) > 0 { 2 } else { 1 };
+                    let osc = format!("\x1b]9;4;{};{}\x07", state, progress.min(100));
+                    if osc != last_osc {
+                        let _ = term.write_str(&osc);
+                        last_osc = osc;
+                    }
+                }
+                if let Some(taskbar) = &taskbar {
+                    let shown = (progress.min(100), failures.load(Ordering::Relaxed) > 0);
+                    if last_taskbar != Some(shown) {
+                        taskbar.set(shown.0, shown.1);
+                        last_taskbar = Some(shown);
+                    }
+                }
+                if was_stalled && stalled.is_none() {
+                    // The warning is longer than the line it replaced
+                    let _ = term.clear_line();
+                }
+                was_stalled = stalled.is_some();
                 let _ = term.write_str(&animation_line);
+                // A shorter name than the last one leaves the end of it behind
+                let _ = term.write_str("\x1b[K");
                 let _ = term.flush();
-                
-                // Configurable animation speed
-                let sleep_duration = if low_animation { 200 } else { 100 };
-                thread::sleep(Duration::from_millis(sleep_duration));
+                drop(frame_guard);
+                let draw_time = draw_start.elapsed();
+
+                // Back off while nothing moves, snap back as soon as progress resumes
+                let seen = Some((current_count, bytes_done, total));
+                interval = if seen == last_seen {
+                    (interval * 2).min(MAX_IDLE_INTERVAL)
+                } else {
+                    base_interval
+                };
+                last_seen = seen;
+
+                // Slow terminals (e.g. over SSH) get fewer frames instead of a backlog
+                thread::park_timeout(interval.max(draw_time * 4));
                 frame += 1;
             }
         });
@@ -156,274 +834,3455 @@ impl AnimatedProgress {
     }
 
     fn increment(&self) {
-        let mut current = self.current.lock().unwrap();
-        *current += 1;
+        self.current.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Print `line` above the progress line: the animation is cleared first
+    /// and redrawn underneath straight away, so messages never land inside it
+    fn say(&self, line: impl std::fmt::Display) {
+        let _drawing = self.drawing.lock();
+        if self.animation_handle.is_some() && self.term.is_term() && !accessible() {
+            let _ = self.term.clear_line();
+        }
+        println!("{line}");
+        if let Some(handle) = &self.animation_handle {
+            handle.thread().unpark();
+        }
+    }
+
+    /// Count a failed file, returning the number of failures so far
+    fn record_failure(&self) -> usize {
+        self.failures.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Remember why `file` failed, for status reports
+    fn set_last_failure(&self, file: &Path, error: &io::Error) {
+        if let Ok(mut last) = self.last_failure.lock() {
+            *last = Some(status::Failure { file: file.to_path_buf(), error: error.to_string() });
+        }
+    }
+
+    /// A previously failed file succeeded on retry
+    fn clear_failure(&self) {
+        self.failures.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn failure_count(&self) -> usize {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    /// Totals that grow as a background scan discovers files
+    fn scan_progress(&self) -> ScanProgress {
+        self.scanning.store(true, Ordering::Relaxed);
+        ScanProgress {
+            total: Arc::clone(&self.total),
+            total_bytes: Arc::clone(&self.total_bytes),
+            scanning: Arc::clone(&self.scanning),
+        }
+    }
+
+    /// Remember which file is being copied, for status reports
+    fn set_current_file(&self, file: &Path) {
+        if let Ok(mut current) = self.current_file.lock() {
+            *current = Some(file.to_path_buf());
+        }
+    }
+
+    /// Counters for the status line printed on request (SIGINFO) and --serve-progress
+    fn status(&self) -> status::Status {
+        status::Status {
+            current: Arc::clone(&self.current),
+            total: Arc::clone(&self.total),
+            bytes: Arc::clone(&self.bytes),
+            total_bytes: Arc::clone(&self.total_bytes),
+            start_time: self.start_time,
+            current_file: Arc::clone(&self.current_file),
+            throughput: Arc::clone(&self.throughput),
+            failures: Arc::clone(&self.failures),
+            last_failure: Arc::clone(&self.last_failure),
+            drawing: Arc::clone(&self.drawing),
+        }
+    }
+
+    /// Shared byte counter, for copies that report progress while streaming
+    fn byte_counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.bytes)
     }
 
     fn stop_animation(&mut self) {
         self.should_stop.store(true, Ordering::Relaxed);
         if let Some(handle) = self.animation_handle.take() {
+            handle.thread().unpark();
             let _ = handle.join();
+            if self.terminal_title {
+                // Restore the title saved in start_animation
+                let _ = self.term.write_str("\x1b[23;0t");
+            }
+            if self.osc_progress {
+                // Remove the terminal's progress indicator
+                let _ = self.term.write_str("\x1b]9;4;0;0\x07");
+            }
+            if self.altscreen {
+                // Back to the user's screen and scrollback, for the summary
+                let _ = self.term.write_str("\x1b[?1049l");
+            }
         }
     }
 
     fn finish(&mut self) {
         self.stop_animation();
         
-        let current_count = *self.current.lock().unwrap();
+        let current_count = self.current.load(Ordering::Relaxed);
         let elapsed = self.start_time.elapsed();
         
         // Clear the animation line
-        let _ = self.term.write_str("\r");
-        let _ = self.term.clear_line();
+        if !accessible() {
+            let _ = self.term.write_str("\r");
+            let _ = self.term.clear_line();
+        }
         
         // Show completion message with celebration effects
-        let completion_line = format!(
-            "🎉 {} {} files copied in {:.2}s! {} 🎊\n",
-            style("SUCCESS!").green().bold(),
-            style(current_count).cyan().bold(),
-            elapsed.as_secs_f32(),
-            style("COMPLETE").magenta().bold()
-        );
+        let completion_line = match &self.templates.completed {
+            Some(template) => format!("{}\n", i18n::fill(template, &self.template_args(&[
+                ("files", current_count.to_string()),
+            ]))),
+            None => format!(
+                "{}{} {} {}{}\n",
+                icon!("🎉 "),
+                style(tr!("SUCCESS!")).green().bold(),
+                tr!("{count} files copied in {seconds}s!",
+                    count = style(self.tally.copied + self.tally.renamed).cyan().bold(),
+                    seconds = format!("{:.2}", elapsed.as_secs_f32())
+                ),
+                style(tr!("COMPLETE")).magenta().bold(),
+                icon!(" 🎊")
+            ) + &self.results().unwrap_or_default(),
+        };
         
         let _ = self.term.write_str(&completion_line);
         let _ = self.term.flush();
+
+        if self.bell {
+            bell::ring(&self.term, self.failures.load(Ordering::Relaxed) == 0);
+        }
+    }
+
+    /// What became of the files, when the run did more than copy each one
+    fn results(&self) -> Option<String> {
+        let tally = &self.tally;
+        let failed = self.failures.load(Ordering::Relaxed);
+        if tally.skipped + tally.unchanged + tally.renamed + tally.unstable + failed == 0 {
+            return None;
+        }
+        let mut parts = vec![tr!("{count} copied", count = style(tally.copied).green().bold())];
+        if tally.unchanged > 0 {
+            parts.push(tr!("{count} up to date", count = style(tally.unchanged).cyan()));
+        }
+        if tally.renamed > 0 {
+            parts.push(tr!("{count} renamed", count = style(tally.renamed).cyan()));
+        }
+        if tally.skipped > 0 {
+            parts.push(tr!("{count} skipped", count = style(tally.skipped).yellow()));
+        }
+        if tally.unstable > 0 {
+            parts.push(tr!("{count} unstable", count = style(tally.unstable).yellow()));
+        }
+        parts.push(tr!("{count} failed", count = style(failed).red()));
+        Some(format!("{}{} {}\n", icon!("📋 "), style(tr!("Results:")).cyan().bold(), parts.join(", ")))
+    }
+
+    /// Placeholders for a summary template: `args` plus the run's size,
+    /// duration and rate so far
+    fn template_args(&self, args: &[(&'static str, String)]) -> Vec<(&'static str, String)> {
+        let bytes = self.bytes.load(Ordering::Relaxed);
+        let elapsed = self.start_time.elapsed();
+        let mut args = args.to_vec();
+        args.push(("copied", self.tally.copied.to_string()));
+        args.push(("skipped", self.tally.skipped.to_string()));
+        args.push(("unchanged", self.tally.unchanged.to_string()));
+        args.push(("renamed", self.tally.renamed.to_string()));
+        args.push(("unstable", self.tally.unstable.to_string()));
+        args.push(("failed", self.failures.load(Ordering::Relaxed).to_string()));
+        args.push(("size", format_size(bytes as f64)));
+        args.push(("seconds", format!("{:.2}", elapsed.as_secs_f32())));
+        args.push(("rate", format_rate(bytes as f64 / elapsed.as_secs_f64().max(0.001))));
+        args
     }
 
-    fn interrupted(&mut self) {
+    fn interrupted(&mut self, reason: StopReason) {
         self.stop_animation();
         
-        let current_count = *self.current.lock().unwrap();
+        let current_count = self.current.load(Ordering::Relaxed);
         let elapsed = self.start_time.elapsed();
         
         // Clear the animation line
-        let _ = self.term.write_str("\r");
-        let _ = self.term.clear_line();
+        if !accessible() {
+            let _ = self.term.write_str("\r");
+            let _ = self.term.clear_line();
+        }
         
         // Show interruption message
-        let interruption_line = format!(
-            "\n🛑 {} Operation interrupted after {:.2}s\n📊 Progress: {}/{} files copied\n⚠️  {} Some files may be partially copied\n",
-            style("INTERRUPTED!").red().bold(),
-            elapsed.as_secs_f32(),
-            style(current_count).yellow().bold(),
-            style(self.total).yellow().bold(),
-            style("WARNING:").yellow().bold()
-        );
+        let (icon, title, detail) = match reason {
+            StopReason::Interrupted => (icon!("🛑 "), tr!("INTERRUPTED!"), String::from(tr!("Operation interrupted"))),
+            StopReason::Timeout => (icon!("⏰ "), tr!("TIMEOUT!"), String::from(tr!("Time limit reached, operation stopped"))),
+            StopReason::FailFast => (icon!("❌ "), tr!("FAILED!"), String::from(tr!("Stopped at the first failed file (--fail-fast)"))),
+            StopReason::MaxErrors(limit) => (icon!("🧯 "), tr!("TOO MANY ERRORS!"),
+                tr!("{failed} files failed (limit {limit}), operation stopped",
+                    failed = self.failures.load(Ordering::Relaxed),
+                    limit = limit
+                )
+            ),
+        };
+        let interruption_line = match &self.templates.interrupted {
+            Some(template) => format!("\n{}\n", i18n::fill(template, &self.template_args(&[
+                ("reason", detail),
+                ("files", current_count.to_string()),
+                ("total", self.total.load(Ordering::Relaxed).to_string()),
+            ]))),
+            None => format!(
+                "\n{}\n{}{}\n{}{} {}\n",
+                tr!("{headline} after {seconds}s",
+                    headline = format!("{}{} {}", icon, style(title).red().bold(), detail),
+                    seconds = format!("{:.2}", elapsed.as_secs_f32())
+                ),
+                icon!("📊 "),
+                tr!("Progress: {done}/{total} files copied",
+                    done = style(current_count).yellow().bold(),
+                    total = style(self.total.load(Ordering::Relaxed)).yellow().bold()
+                ),
+                icon!("⚠️  "),
+                style(tr!("WARNING:")).yellow().bold(),
+                tr!("Some files may be partially copied")
+            ) + &self.results().unwrap_or_default(),
+        };
         
         let _ = self.term.write_str(&interruption_line);
         let _ = self.term.flush();
+
+        if self.bell {
+            bell::ring(&self.term, false);
+        }
     }
 }
 
-fn setup_signal_handler() -> Result<(mpsc::Receiver<()>, Arc<AtomicBool>)> {
-    let (tx, rx) = mpsc::channel();
-    let interrupted = Arc::new(AtomicBool::new(false));
-    let interrupted_clone = Arc::clone(&interrupted);
-    
-    thread::spawn(move || {
-        let mut signals = Signals::new(&[SIGINT]).expect("Failed to register signal handler");
-        for _ in signals.forever() {
-            interrupted_clone.store(true, Ordering::Relaxed);
-            let _ = tx.send(());
-            break;
-        }
-    });
-    
-    Ok((rx, interrupted))
+/// Live totals fed by a scan running alongside the copy
+struct ScanProgress {
+    total: Arc<AtomicUsize>,
+    total_bytes: Arc<AtomicU64>,
+    scanning: Arc<AtomicBool>,
 }
 
-fn copy_file_with_temp(source: &Path, destination: &Path) -> std::io::Result<u64> {
-    // Create temporary file name
-    let temp_dest = destination.with_extension(
-        format!("{}.tmp", 
-            destination.extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or("tmp")
-        )
-    );
-    
-    // Copy to temporary file first
-    let bytes_copied = fs::copy(source, &temp_dest)?;
-    
-    // Rename temporary file to final destination (atomic operation)
-    fs::rename(&temp_dest, destination)?;
-    
-    Ok(bytes_copied)
+impl ScanProgress {
+    fn found(&self, bytes: u64) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        self.total_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn finished(&self) {
+        self.scanning.store(false, Ordering::Relaxed);
+    }
 }
 
-fn collect_files(path: &Path) -> Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
-    if path.is_file() {
-        files.push(path.to_path_buf());
-    } else if path.is_dir() {
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                files.push(path);
-            } else if path.is_dir() {
-                files.extend(collect_files(&path)?);
-            }
-        }
+/// A run that ends early through an error still gives the terminal back its
+/// title and main screen
+impl Drop for AnimatedProgress {
+    fn drop(&mut self) {
+        self.stop_animation();
     }
-    Ok(files)
 }
 
-fn total_size(files: &[PathBuf]) -> u64 {
-    files.iter()
-        .filter_map(|f| fs::metadata(f).ok())
-        .map(|m| m.len())
-        .sum()
+/// Spinner with live counts while the source is enumerated before the banner
+struct ScanSpinner {
+    found: Arc<AtomicUsize>,
+    bytes: Arc<AtomicU64>,
+    should_stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+impl ScanSpinner {
+    fn start() -> Self {
+        let found = Arc::new(AtomicUsize::new(0));
+        let bytes = Arc::new(AtomicU64::new(0));
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let term = Term::stdout();
 
-    // Set up signal handler for graceful shutdown
-    let (interrupt_rx, interrupted) = setup_signal_handler()?;
-
-    let source = cli.source.or(cli.source_positional)
-        .unwrap_or_else(|| {
-            PathBuf::from(
-                Input::<String>::new()
-                    .with_prompt("Enter source path")
-                    .interact_text()
-                    .unwrap()
-            )
+        // Nothing to animate when the output goes to a file or pipe
+        let handle = (term.is_term() && !accessible()).then(|| {
+            let (found, bytes, should_stop) = (Arc::clone(&found), Arc::clone(&bytes), Arc::clone(&should_stop));
+            thread::spawn(move || {
+                let spinner = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+                let mut frame = 0;
+                while !should_stop.load(Ordering::Relaxed) {
+                    if stop_prompt_open() {
+                        thread::park_timeout(Duration::from_millis(100));
+                        continue;
+                    }
+                    let _ = term.write_str(&format!("\r{} {}",
+                        style(spinner[frame % spinner.len()]).green().bold(),
+                        tr!("Scanning... discovered {count} files / {size}",
+                            count = style(found.load(Ordering::Relaxed)).yellow(),
+                            size = style(format_size(bytes.load(Ordering::Relaxed) as f64)).green()
+                        )
+                    ));
+                    let _ = term.flush();
+                    thread::park_timeout(Duration::from_millis(100));
+                    frame += 1;
+                }
+                let _ = term.write_str("\r");
+                let _ = term.clear_line();
+            })
         });
 
-    let destination = cli.destination.or(cli.destination_positional)
-        .unwrap_or_else(|| {
-            PathBuf::from(
-                Input::<String>::new()
-                    .with_prompt("Enter destination path")
-                    .interact_text()
-                    .unwrap()
-            )
-        });
+        Self { found, bytes, should_stop, handle }
+    }
 
-    // Check if source exists
-    if !source.exists() {
-        eprintln!("{} Source path does not exist: {}", 
-            style("❌ ERROR:").red().bold(),
-            style(source.display()).white()
-        );
-        return Ok(());
+    fn found(&self, bytes: u64) {
+        self.found.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
     }
 
-    let files = collect_files(&source)?;
-    let total_bytes = total_size(&files);
-    let file_count = files.len();
-    
-    // Check if there are any files to copy
-    if file_count == 0 {
-        println!("{} No files found to copy from: {}", 
-            style("⚠️ WARNING:").yellow().bold(),
-            style(source.display()).white()
-        );
-        return Ok(());
+    fn finish(mut self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
     }
-    
-    println!("🚀 {} Starting copy operation...", style("INITIALIZING").cyan().bold());
-    println!("📁 Files to copy: {}", style(file_count).yellow().bold());
-    println!("💾 Total size: {} bytes", style(total_bytes).green().bold());
-    println!("💡 Press Ctrl+C to safely stop the operation");
-    println!();
+}
 
-    let mut progress = AnimatedProgress::new(file_count);
-    progress.start_animation(cli.low_animation);
+/// Second progress phase while copies are re-read for --verify
+struct VerifyProgress {
+    done: Arc<AtomicUsize>,
+    bytes: Arc<AtomicU64>,
+    should_stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
 
-    // Small delay to let animation start
-    thread::sleep(Duration::from_millis(200));
+impl VerifyProgress {
+    fn start(files: usize, total_bytes: u64) -> Self {
+        let done = Arc::new(AtomicUsize::new(0));
+        let bytes = Arc::new(AtomicU64::new(0));
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let term = Term::stdout();
 
-    for file in files {
-        // Check for interruption before each file
+        let handle = (term.is_term() && !accessible()).then(|| {
+            let (done, bytes, should_stop) = (Arc::clone(&done), Arc::clone(&bytes), Arc::clone(&should_stop));
+            thread::spawn(move || {
+                let spinner = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+                let mut frame = 0;
+                while !should_stop.load(Ordering::Relaxed) {
+                    if stop_prompt_open() {
+                        thread::park_timeout(Duration::from_millis(100));
+                        continue;
+                    }
+                    let read = bytes.load(Ordering::Relaxed).min(total_bytes);
+                    let percent = (read * 100).checked_div(total_bytes).unwrap_or(0);
+                    let _ = term.write_str(&format!("\r{} {}",
+                        style(spinner[frame % spinner.len()]).cyan().bold(),
+                        tr!("Verifying... {percent}% ({done}/{total} files, {size})",
+                            percent = style(percent).cyan().bold(),
+                            done = style(done.load(Ordering::Relaxed)).yellow(),
+                            total = files,
+                            size = style(format_size(read as f64)).green()
+                        )
+                    ));
+                    let _ = term.flush();
+                    thread::park_timeout(Duration::from_millis(100));
+                    frame += 1;
+                }
+                let _ = term.write_str("\r");
+                let _ = term.clear_line();
+            })
+        });
+
+        Self { done, bytes, should_stop, handle }
+    }
+
+    fn finish(mut self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Re-read every copy and compare it with its source, returning the copies
+/// that differ, or `None` if the run was stopped part way
+fn verify_copies(
+    copied: &[verify::Copied],
+    cache: Option<&hashcache::HashCache>,
+    threads: Option<usize>,
+    interrupted: &AtomicBool,
+) -> Option<Vec<(PathBuf, verify::Mismatch)>> {
+    use rayon::prelude::*;
+
+    let total_bytes = copied.iter().map(verify::Copied::cost).sum();
+    let progress = VerifyProgress::start(copied.len(), total_bytes);
+    // Hashing, not reading, is what limits verification on fast drives
+    let check_all = || -> Vec<_> {
+        copied
+            .par_iter()
+            .map(|file| {
+                let result = file.check(&progress.bytes, interrupted, cache);
+                progress.done.fetch_add(1, Ordering::Relaxed);
+                result
+            })
+            .collect()
+    };
+    // Unless told otherwise, as many readers as the drives on both sides take well
+    let threads = threads.unwrap_or_else(|| match copied.first() {
+        Some(copy) => storage::workers(&[&copy.source, &copy.destination]),
+        None => 1,
+    });
+    let results = match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool.install(check_all),
+        Err(_) => check_all(),
+    };
+    progress.finish();
+
+    let mut mismatches = Vec::new();
+    for (file, result) in copied.iter().zip(results) {
+        match result {
+            Ok(None) => {}
+            Ok(Some(mismatch)) => mismatches.push((file.destination.clone(), mismatch)),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return None,
+            Err(e) => mismatches.push((file.destination.clone(), verify::Mismatch::Unreadable(e.to_string()))),
+        }
+    }
+    Some(mismatches)
+}
+
+/// Parse a duration such as `90`, `90s`, `45m`, `2h`, `1d` or `1h30m`
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return Err(format!("unknown duration unit '{c}' in: {value}")),
+        };
+        let amount: u64 = number.parse().map_err(|_| format!("invalid duration: {value}"))?;
+        total = amount
+            .checked_mul(unit)
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or_else(|| format!("duration too large: {value}"))?;
+        number.clear();
+    }
+    if !number.is_empty() || value.is_empty() {
+        return Err(format!("invalid duration: {value} (expected e.g. 90s, 45m, 2h)"));
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// `text` cut down to `width` columns by dropping its middle, e.g.
+/// `holiday-ph…IMG_0042.jpg`, so the start and the extension both stay visible
+fn shorten_middle(text: &str, width: usize) -> String {
+    if console::measure_text_width(text) <= width {
+        return text.to_string();
+    }
+    let column = |c: char| console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+    let keep = width.saturating_sub(1);
+    let mut tail = Vec::new();
+    let mut tail_width = 0;
+    for c in text.chars().rev() {
+        if tail_width + column(c) > keep.div_ceil(2) {
+            break;
+        }
+        tail_width += column(c);
+        tail.push(c);
+    }
+    let mut head = String::new();
+    let mut head_width = 0;
+    for c in text.chars() {
+        if head_width + column(c) > keep - tail_width {
+            break;
+        }
+        head_width += column(c);
+        head.push(c);
+    }
+    head.push('…');
+    head.extend(tail.iter().rev());
+    head
+}
+
+/// Throughput in the units of format_size, e.g. `3.2 GiB/s`, or `3.4 GB/s` with --si
+fn format_rate(bytes_per_sec: f64) -> String {
+    format!("{}/s", format_size(bytes_per_sec))
+}
+
+/// Size for people, e.g. `3.4 GiB`, or `3.7 GB` with --si. Machine-readable
+/// outputs (manifests, reports) keep exact byte counts.
+fn format_size(bytes: f64) -> String {
+    let (base, units) = if SI_UNITS.load(Ordering::Relaxed) {
+        (1000.0, ["B", "kB", "MB", "GB", "TB", "PB"])
+    } else {
+        (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"])
+    };
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= base && unit < units.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", value as u64)
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+/// Catch Ctrl+C, which asks the run to stop after the file in progress.
+/// With `confirm` (--confirm-stop) it first asks whether that was meant.
+fn setup_signal_handler(confirm: Option<Duration>) -> Result<(mpsc::Receiver<()>, Arc<AtomicBool>)> {
+    let (tx, rx) = mpsc::channel();
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let interrupted_clone = Arc::clone(&interrupted);
+    
+    thread::spawn(move || {
+        let mut signals = Signals::new([SIGINT]).expect("Failed to register signal handler");
+        let mut answers = None;
+        while signals.forever().next().is_some() {
+            // A Ctrl+C once stopping does not wait for the file being copied to finish
+            if interrupted_clone.load(Ordering::Relaxed) {
+                force_stop();
+            }
+            if confirm.is_some_and(|timeout| !confirm_stop(&mut signals, &mut answers, timeout)) {
+                continue;
+            }
+            interrupted_clone.store(true, Ordering::Relaxed);
+            let _ = tx.send(());
+        }
+    });
+    
+    Ok((rx, interrupted))
+}
+
+/// Ask whether Ctrl+C was meant to stop the run. Only "y" stops it; no
+/// answer within `timeout` carries on, and Ctrl+C again counts as yes.
+/// Lines are read from stdin in a thread of their own that outlives the
+/// question, so `answers` keeps it for the next one.
+fn confirm_stop(signals: &mut Signals, answers: &mut Option<mpsc::Receiver<String>>, timeout: Duration) -> bool {
+    use std::io::IsTerminal;
+    // Nobody to answer
+    if !io::stdin().is_terminal() {
+        return true;
+    }
+    let answers = answers.get_or_insert_with(|| {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lines() {
+                if tx.send(line.unwrap_or_default()).is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    });
+    // Whatever was typed before the question is no answer to it
+    while answers.try_recv().is_ok() {}
+
+    STOP_PROMPT.store(true, Ordering::Relaxed);
+    let stdout = Term::stdout();
+    if stdout.is_term() {
+        let _ = stdout.write_str("\r");
+        let _ = stdout.clear_line();
+    }
+    let term = Term::stderr();
+    let _ = term.write_str(&format!("{}{} ",
+        style(icon!("⏸️  ")).yellow().bold(),
+        tr!("Stop copying? [y/N] (carrying on in {seconds}s)", seconds = timeout.as_secs())
+    ));
+    let asked = Instant::now();
+    let stop = loop {
+        if signals.pending().next().is_some() {
+            let _ = term.write_line("");
+            break true;
+        }
+        match answers.recv_timeout(Duration::from_millis(100)) {
+            Ok(answer) => break matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+            Err(mpsc::RecvTimeoutError::Timeout) if asked.elapsed() < timeout => {}
+            Err(_) => {
+                let _ = term.write_line("");
+                break false;
+            }
+        }
+    };
+    if !stop {
+        let _ = term.write_line(&format!("{}{}", icon!("▶️  "), tr!("Carrying on")));
+    }
+    STOP_PROMPT.store(false, Ordering::Relaxed);
+    stop
+}
+
+/// End the run at once: remove the temporary files still being written, so
+/// the destination is not left littered, restore the terminal and exit
+fn force_stop() -> ! {
+    let removed = IN_FLIGHT
+        .lock()
+        .map(|mut in_flight| in_flight.drain(..).filter(|temp| fs::remove_file(temp).is_ok()).count())
+        .unwrap_or(0);
+    let term = Term::stdout();
+    if term.is_term() {
+        // Leave the alternate screen of --altscreen and bring the cursor back
+        let _ = term.write_str("\x1b[?1049l");
+        let _ = term.show_cursor();
+    }
+    eprintln!("\n{}{} {}", icon!("🛑 "),
+        style(tr!("STOPPED!")).red().bold(),
+        tr!("Ctrl+C pressed twice: stopped without finishing the current file ({count} temporary files removed)", count = removed)
+    );
+    lock::release_all();
+    vss::release_all();
+    std::process::exit(130);
+}
+
+fn temp_path(destination: &Path) -> PathBuf {
+    destination.with_extension(
+        format!("{}.tmp", 
+            destination.extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or("tmp")
+        )
+    )
+}
+
+fn copy_file_with_temp(source: &Path, destination: &Path) -> std::io::Result<u64> {
+    // Create temporary file name
+    let temp_dest = temp_path(destination);
+    
+    // Copy to temporary file first
+    let bytes_copied = noatime::copy(source, &temp_dest)?;
+    
+    // Rename temporary file to final destination (atomic operation)
+    fs::rename(&temp_dest, destination)?;
+    
+    Ok(bytes_copied)
+}
+
+fn link_file_with_temp(source: &Path, destination: &Path) -> std::io::Result<u64> {
+    // Link under a temporary name so an existing destination is replaced atomically
+    let temp_dest = temp_path(destination);
+    let _ = fs::remove_file(&temp_dest);
+    fs::hard_link(source, &temp_dest)?;
+    fs::rename(&temp_dest, destination)?;
+
+    Ok(fs::metadata(source)?.len())
+}
+
+/// Open `source` positioned at `offset`, yielding at most `length` bytes
+fn open_range(source: &Path, offset: u64, length: u64) -> io::Result<io::Take<fs::File>> {
+    let mut reader = noatime::open(source)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    Ok(reader.take(length))
+}
+
+/// Copy everything `reader` yields into `target`, reporting progress as it
+/// streams and stopping early on interruption.
+///
+/// With a non-zero `resume_from`, the first `resume_from` bytes are assumed to
+/// already be in `target` and the rest is appended. With a `checkpoint`, data is
+/// flushed to disk and recorded after every chunk.
+fn stream_copy(
+    mut reader: impl Read,
+    target: &Path,
+    resume_from: u64,
+    checkpoint: Option<&Checkpoint>,
+    counter: &AtomicU64,
+    interrupted: &AtomicBool,
+) -> io::Result<u64> {
+
+    let mut writer = if resume_from > 0 {
+        let mut file = fs::OpenOptions::new().write(true).open(target)?;
+        file.set_len(resume_from)?;
+        file.seek(SeekFrom::Start(resume_from))?;
+        file
+    } else {
+        fs::File::create(target)?
+    };
+    counter.fetch_add(resume_from, Ordering::Relaxed);
+
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut written = resume_from;
+    let mut unrecorded = 0u64;
+    loop {
         if interrupted.load(Ordering::Relaxed) {
-            progress.interrupted();
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "copy interrupted"));
+        }
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        written += read as u64;
+        counter.fetch_add(read as u64, Ordering::Relaxed);
+
+        if let Some(checkpoint) = checkpoint {
+            unrecorded += read as u64;
+            if unrecorded >= checkpoint.chunk_size {
+                writer.sync_data()?;
+                checkpoint.record(written)?;
+                unrecorded = 0;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(written)
+}
+
+/// Number of bytes at the start of `partial` that can be kept when resuming:
+/// its whole length if it matches the same prefix of the source range, else 0.
+fn resumable_prefix(source: &Path, (offset, length): (u64, u64), partial: &Path) -> io::Result<u64> {
+    let existing = match fs::metadata(partial) {
+        Ok(meta) if meta.is_file() => meta.len(),
+        _ => return Ok(0),
+    };
+    if existing == 0 || existing > length {
+        return Ok(0);
+    }
+    let matches = hash_range(source, offset, existing)? == hash_range(partial, 0, existing)?;
+    Ok(if matches { existing } else { 0 })
+}
+
+/// Length of the temporary file `temp` when it holds the start of `source`,
+/// so the copy can go on from there; 0 when there is none, or it holds
+/// something else. `hasher`, which has seen nothing yet, is given the start.
+fn leftover_prefix(source: &Path, temp: &Path, hasher: Option<&mut blake3::Hasher>) -> io::Result<u64> {
+    let existing = match fs::metadata(temp) {
+        Ok(meta) if meta.is_file() => meta.len(),
+        _ => return Ok(0),
+    };
+    if existing == 0 || existing > fs::metadata(source)?.len() {
+        return Ok(0);
+    }
+    let mut prefix = blake3::Hasher::new();
+    prefix.update_reader(noatime::open(source)?.take(existing))?;
+    if prefix.finalize() != hash_range(temp, 0, existing)? {
+        return Ok(0);
+    }
+    if let Some(hasher) = hasher {
+        *hasher = prefix;
+    }
+    Ok(existing)
+}
+
+/// Give `target`, written by stream_copy, the permissions of `source`, as fs::copy does
+fn copy_permissions(source: &Path, target: &Path) -> io::Result<()> {
+    fs::set_permissions(target, fs::metadata(source)?.permissions())
+}
+
+fn hash_range(path: &Path, offset: u64, length: u64) -> io::Result<blake3::Hash> {
+    let mut file = noatime::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(file.take(length))?;
+    Ok(hasher.finalize())
+}
+
+/// Parse a percentage such as `5%`, `0.5%` or `20`
+fn parse_percent(value: &str) -> Result<f64, String> {
+    let number = value.trim().trim_end_matches('%');
+    match number.parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err(format!("invalid percentage: {value} (expected e.g. 5%)")),
+    }
+}
+
+/// Parse a byte size such as `4096`, `512K`, `2GiB` or `1MB`.
+///
+/// Like dd, bare `K/M/G/T` (and `KiB`...) are binary multiples while `KB/MB/...` are decimal.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().map_err(|_| format!("invalid size: {value}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KIB" => 1 << 10,
+        "M" | "MIB" => 1 << 20,
+        "G" | "GIB" => 1 << 30,
+        "T" | "TIB" => 1 << 40,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "TB" => 1_000_000_000_000,
+        _ => return Err(format!("unknown size unit in: {value}")),
+    };
+    number.checked_mul(multiplier).ok_or_else(|| format!("size too large: {value}"))
+}
+
+fn symlink_file_with_temp(source: &Path, destination: &Path, kind: SymlinkKind) -> std::io::Result<u64> {
+    let absolute_source = fs::canonicalize(source)?;
+    let target = match kind {
+        SymlinkKind::Absolute => absolute_source.clone(),
+        SymlinkKind::Relative => {
+            let parent = destination.parent().unwrap_or_else(|| Path::new("."));
+            let parent = fs::canonicalize(if parent.as_os_str().is_empty() { Path::new(".") } else { parent })?;
+            relative_path(&parent, &absolute_source)
+        }
+    };
+
+    let temp_dest = temp_path(destination);
+    let _ = fs::remove_file(&temp_dest);
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&target, &temp_dest)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_file(&target, &temp_dest)?;
+    fs::rename(&temp_dest, destination)?;
+
+    Ok(fs::metadata(&absolute_source)?.len())
+}
+
+/// Create a symlink at `destination` with the same (dangling) target as the link `source`
+fn recreate_symlink(source: &Path, destination: &Path) -> std::io::Result<u64> {
+    let target = fs::read_link(source)?;
+    let temp_dest = temp_path(destination);
+    let _ = fs::remove_file(&temp_dest);
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&target, &temp_dest)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_file(&target, &temp_dest)?;
+    fs::rename(&temp_dest, destination)?;
+
+    Ok(0)
+}
+
+/// Path that leads from directory `from` to `to`; both must be absolute
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+/// Where `file` (found under `source`) ends up inside `destination`
+fn destination_for(file: &Path, source: &Path, destination: &Path) -> PathBuf {
+    if source.is_file() {
+        if destination.is_dir() {
+            destination.join(
+                source.file_name().unwrap_or_else(|| std::ffi::OsStr::new("unknown"))
+            )
+        } else {
+            destination.to_path_buf()
+        }
+    } else {
+        destination.join(file.strip_prefix(source).unwrap_or(file))
+    }
+}
+
+/// Final destination path of `file`, adjusted for encryption (`.age` names)
+/// and decompression (compression extension dropped)
+fn output_path(file: &Path, sources: &Sources, destination: &Path, crypto: Option<&Crypto>, decompress: bool) -> PathBuf {
+    let dest_path = match sources.renamed(file) {
+        Some(relative) => destination.join(relative),
+        None => destination_for(file, sources.root_of(file), destination),
+    };
+    match crypto {
+        Some(crypto) => crypto.output_path(dest_path),
+        None if decompress => compress::output_path(dest_path),
+        None => dest_path,
+    }
+}
+
+/// Whether `error` means another program holds the file open without sharing
+/// it (Windows sharing or lock violation)
+fn in_use(error: &io::Error) -> bool {
+    #[cfg(windows)]
+    {
+        const ERROR_SHARING_VIOLATION: i32 = 32;
+        const ERROR_LOCK_VIOLATION: i32 = 33;
+        matches!(error.raw_os_error(), Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION))
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = error;
+        false
+    }
+}
+
+/// Whether both paths refer to the same file or directory on disk
+fn same_file(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let (Ok(a), Ok(b)) = (fs::metadata(a), fs::metadata(b)) {
+            return a.dev() == b.dev() && a.ino() == b.ino();
+        }
+        false
+    }
+    #[cfg(not(unix))]
+    {
+        matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+    }
+}
+
+/// Whether `path` is `file`, whose metadata `source` the walk already read
+fn is_same_file(file: &Path, source: Option<&fs::Metadata>, path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let _ = file;
+        match (source, fs::metadata(path)) {
+            (Some(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = source;
+        same_file(file, path)
+    }
+}
+
+/// Whether `file` no longer has the size or modification time the walk
+/// read (`before`); a file gone since is not taken as changed
+fn source_changed(file: &Path, before: &fs::Metadata) -> bool {
+    fs::metadata(file).is_ok_and(|now| now.len() != before.len() || now.modified().ok() != before.modified().ok())
+}
+
+/// Whether `destination` (or its nearest existing ancestor) is on the same
+/// filesystem as `source`
+fn same_filesystem(source: &Path, destination: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let Ok(source_meta) = fs::metadata(source) else {
+            return false;
+        };
+        destination.ancestors()
+            .find_map(|p| fs::metadata(p).ok())
+            .is_some_and(|dest_meta| dest_meta.dev() == source_meta.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        // No portable device id; let hard_link report cross-device errors per file
+        let _ = (source, destination);
+        true
+    }
+}
+
+/// Absolute, symlink-free form of `path`, which does not need to exist yet:
+/// the deepest existing ancestor is canonicalized and the rest appended.
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            // Missing directories will be real ones, so `..` among them is lexical
+            return missing.iter().rev().fold(resolved, |mut acc, part: &std::ffi::OsString| {
+                if part == ".." {
+                    acc.pop();
+                } else {
+                    acc.push(part);
+                }
+                acc
+            });
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(Component::Normal(name))) => {
+                missing.push(name.to_os_string());
+                existing = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            }
+            (Some(parent), Some(Component::ParentDir)) => {
+                missing.push("..".into());
+                existing = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            }
+            _ => return std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+        }
+    }
+}
+
+/// Whether writing `path` would land outside `root` once `..` is applied and
+/// the symlinks already on disk are followed. A symlink at `path` itself only
+/// counts with `follow_last`, for copies that write through it rather than
+/// replacing it.
+fn escapes(path: &Path, root: &Path, follow_last: bool) -> bool {
+    if path == root {
+        return false;
+    }
+    let resolved = match (path.parent(), path.components().next_back()) {
+        (Some(parent), Some(Component::Normal(name))) if !follow_last => {
+            resolve_path(if parent.as_os_str().is_empty() { Path::new(".") } else { parent }).join(name)
+        }
+        _ => resolve_path(path),
+    };
+    !resolved.starts_with(resolve_path(root))
+}
+
+fn extension_key(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| String::from("(none)"))
+}
+
+/// Report what the walk left out of the copy, e.g. symlink loops
+fn print_walk_notes(notes: &walk::Notes) {
+    let mut unreadable = Vec::new();
+    for (path, note) in notes.take() {
+        match note {
+            walk::Note::Unreadable { error } => unreadable.push((path, error)),
+            walk::Note::Loop { ancestor } => println!("{} {}",
+                style(icon!("🔁 ", "Symlink loop:")).yellow().bold(),
+                tr!("{path} leads back to {ancestor} (not followed)",
+                    path = style(path.display()).white(),
+                    ancestor = ancestor.display()
+                )
+            ),
+            walk::Note::Broken { target } => println!("{} {}",
+                style(icon!("🔗 ", "Broken symlink:")).yellow().bold(),
+                tr!("{path} → {target} (target missing, not copied)",
+                    path = style(path.display()).white(),
+                    target = target.display()
+                )
+            ),
+        }
+    }
+    if !unreadable.is_empty() {
+        println!("{} {}",
+            style(icon!("🚫 ", "UNREADABLE:")).red().bold(),
+            tr!("{count} directories could not be read and were not copied:", count = style(unreadable.len()).yellow())
+        );
+        for (path, error) in unreadable {
+            println!("   {} ({})", path.display(), style(error).red());
+        }
+    }
+}
+
+/// Report how overlapping paths between merged sources were settled
+fn print_merge_decisions(decisions: &[merge::Decision]) {
+    if decisions.is_empty() {
+        return;
+    }
+    println!("{} {}",
+        style(icon!("🔀 ", "MERGE:")).cyan().bold(),
+        tr!("{count} paths exist in more than one source", count = style(decisions.len()).yellow())
+    );
+    for decision in decisions {
+        println!("   {} ← {}", decision.relative.display(), style(decision.kept.display()).green());
+        for (file, renamed) in &decision.others {
+            match renamed {
+                Some(relative) => println!("   {} ← {}", relative.display(), style(file.display()).cyan()),
+                None => println!("     {} {}", style(tr!("skipped")).dim(), file.display()),
+            }
+        }
+    }
+}
+
+fn print_extension_summary(by_extension: &HashMap<String, (usize, u64)>) {
+    let mut rows: Vec<_> = by_extension.iter().collect();
+    // Largest groups first so the bulk of the import is at the top
+    rows.sort_by(|a, b| b.1.1.cmp(&a.1.1).then_with(|| a.0.cmp(b.0)));
+
+    let width = rows.iter().map(|(ext, _)| ext.len()).max().unwrap_or(0).max(9);
+    println!("{}{}", icon!("📊 "), style(tr!("By extension:")).cyan().bold());
+    for (ext, (count, bytes)) in rows {
+        println!("   {:<width$}  {:>14}  {:>10}",
+            style(ext).white(),
+            tr!("{count} files", count = style(count).yellow()),
+            style(format_size(*bytes as f64)).green(),
+            width = width
+        );
+    }
+}
+
+/// List the files that copied far below the run's median rate, which tends to
+/// point at a failing sector or a cold network cache. Small files are left
+/// out, since their time is mostly per-file overhead.
+fn print_slow_files(timings: &[(PathBuf, u64, Duration)]) {
+    let rate = |(_, bytes, elapsed): &(PathBuf, u64, Duration)| *bytes as f64 / elapsed.as_secs_f64().max(0.001);
+    let timed: Vec<_> = timings.iter().filter(|(_, bytes, _)| *bytes >= SLOW_FILE_MIN_BYTES).collect();
+    if timed.len() < SLOW_FILE_MIN_SAMPLES {
+        return;
+    }
+    let mut rates: Vec<f64> = timed.iter().map(|t| rate(t)).collect();
+    rates.sort_by(f64::total_cmp);
+    let median = rates[rates.len() / 2];
+
+    let slow: Vec<_> = timed.into_iter().filter(|t| rate(t) * SLOW_FILE_FACTOR < median).collect();
+    if slow.is_empty() {
+        return;
+    }
+    println!("{}{} {}", icon!("🐢 "),
+        style(tr!("Slow files:")).yellow().bold(),
+        tr!("{count} files copied far below the median rate of {rate}",
+            count = style(slow.len()).yellow(),
+            rate = format_rate(median)
+        )
+    );
+    for timing in slow {
+        let (file, bytes, elapsed) = timing;
+        println!("   {} {} ({})",
+            style(file.display()).white(),
+            style(format_rate(rate(timing))).red(),
+            tr!("{size} in {seconds}s", size = format_size(*bytes as f64), seconds = format!("{:.2}", elapsed.as_secs_f32()))
+        );
+    }
+}
+
+/// Whether the run should keep going after a file
+enum Flow {
+    Continue,
+    Stop,
+}
+
+/// State of one copy run, shared by the main pass and the retry passes
+struct Run<'a> {
+    cli: &'a CopyArgs,
+    sources: &'a Sources,
+    progress: AnimatedProgress,
+    byte_counter: Arc<AtomicU64>,
+    interrupted: Arc<AtomicBool>,
+    stop_reason: Arc<OnceLock<StopReason>>,
+    streamed: Option<(u64, u64)>,
+    extension_stats: HashMap<String, (usize, u64)>,
+    /// Size and duration of every copy, for the slow-file report (-v, --by-extension)
+    timings: Vec<(PathBuf, u64, Duration)>,
+    failed: Vec<FailedFile<'a>>,
+    in_use: Vec<PathBuf>,
+    /// Files whose source changed while they were copied, for --settle
+    unstable: Vec<FailedFile<'a>>,
+    /// Copies to re-read with --verify once the run is done
+    copied: Vec<verify::Copied>,
+    /// Sources copied by copro mv, removed once the run is done
+    moved: Vec<PathBuf>,
+    print0: Option<print0::Print0>,
+    manifest: Option<Manifest>,
+    catalog: Option<catalog::Catalog>,
+    /// A catalog write has failed and been reported
+    catalog_failed: Cell<bool>,
+    csv_report: Option<report::CsvReport>,
+    html_report: Option<report::HtmlReport>,
+    crypto: Option<&'a Crypto>,
+    undo: Option<undo::UndoLog>,
+    watchdog: watchdog::Watchdog,
+    /// Hashes kept between runs, for --verify and --checksum
+    hash_cache: Option<hashcache::HashCache>,
+    sync_state: Option<syncstate::SyncState>,
+    /// Directories already given their --chmod/--chown attributes
+    attributed_dirs: HashSet<PathBuf>,
+    /// Destination directories known to exist, so a deep tree of small
+    /// files does not cost a create_dir_all per file
+    created_dirs: RefCell<HashSet<PathBuf>>,
+}
+
+/// A file whose latest copy attempt failed (or, in `Run::unstable`, read a
+/// source that was changing)
+struct FailedFile<'a> {
+    source: PathBuf,
+    destination: PathBuf,
+    error: String,
+    cli: &'a CopyArgs,
+}
+
+/// What copying one file produced
+struct Transferred {
+    bytes: u64,
+    /// Hash of the data, when it was hashed on the way
+    hash: Option<blake3::Hash>,
+    /// The source changed between the walk and the end of the copy
+    unstable: bool,
+}
+
+/// A file waiting to be copied, and the options it is copied with (which
+/// differ between jobs of --jobs-from)
+struct Pending<'a> {
+    file: PathBuf,
+    /// What the walk read of `file`; none for a dangling symlink
+    metadata: Option<fs::Metadata>,
+    destination: PathBuf,
+    /// The destination tree `destination` has to stay inside
+    root: &'a Path,
+    cli: &'a CopyArgs,
+}
+
+impl<'a> Run<'a> {
+    fn stop_reason(&self) -> StopReason {
+        self.stop_reason.get().copied().unwrap_or(StopReason::Interrupted)
+    }
+
+    /// Give the directories between `root` and `path` the --chmod directory
+    /// mode and the --chown owner, creating them first; each one once
+    fn attribute_directories(&mut self, path: &Path, root: &Path) -> io::Result<()> {
+        let mode = self.cli.chmod.and_then(|modes| modes.dir);
+        for dir in path.ancestors().skip(1).take_while(|dir| *dir != root && dir.starts_with(root)) {
+            if self.attributed_dirs.insert(dir.to_path_buf()) {
+                fs::create_dir_all(dir)?;
+                ownership::apply(dir, mode, self.cli.chown)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the directory `path` goes in, unless this run already has
+    fn create_parent(&self, path: &Path) -> io::Result<()> {
+        let Some(parent) = path.parent() else {
+            return Ok(());
+        };
+        if self.created_dirs.borrow().contains(parent) {
+            return Ok(());
+        }
+        fs::create_dir_all(parent)?;
+        self.created_dirs.borrow_mut().insert(parent.to_path_buf());
+        Ok(())
+    }
+
+    /// Note what became of `file` in the --catalog and the reports
+    fn record(&mut self, file: &Path, destination: &Path, size: u64, elapsed: Duration, outcome: catalog::Outcome) {
+        if let Some(catalog) = &self.catalog {
+            self.note_catalog_error(catalog.record(file, destination, size, elapsed.as_secs_f64(), outcome));
+        }
+        if let Some(report) = &mut self.html_report {
+            report.row(file, destination, size, elapsed, outcome);
+        }
+        let Some(Err(e)) = self.csv_report.as_mut().map(|report| report.row(file, destination, size, elapsed, outcome)) else {
+            return;
+        };
+        if let Some(report) = self.csv_report.take() {
+            self.progress.say(format!("{} {}",
+                style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+                tr!("Could not write the CSV report {path}: {error}", path = report.path.display(), error = e)
+            ));
+        }
+    }
+
+    /// Warn about the first catalog write that fails; the copy carries on
+    /// regardless, and later failures would only repeat it
+    fn note_catalog_error(&self, result: io::Result<()>) {
+        match result {
+            Err(e) if !self.catalog_failed.replace(true) => self.progress.say(format!("{} {}",
+                style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+                tr!("Could not write to the run catalog: {error}", error = e)
+            )),
+            _ => {}
+        }
+    }
+
+    /// copro mv: remove the sources that were copied, then the source
+    /// directories that this left empty. Sources whose copy failed verification stay.
+    fn remove_moved(&mut self) {
+        if !self.cli.remove_sources {
+            return;
+        }
+        let mut removed = 0;
+        let mut emptied = HashSet::new();
+        for source in std::mem::take(&mut self.moved) {
+            match fs::remove_file(&source) {
+                Ok(()) => {
+                    removed += 1;
+                    if let Some(dir) = source.parent() {
+                        emptied.insert(dir.to_path_buf());
+                    }
+                }
+                Err(e) => println!("{} {} ({})",
+                    style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+                    tr!("Could not remove the source {path}", path = style(source.display()).white()),
+                    e
+                ),
+            }
+        }
+        remove_emptied_dirs(emptied, &self.sources.roots);
+        println!("{}{}", icon!("🚚 "), tr!("{count} source files removed after copying", count = style(removed).cyan().bold()));
+    }
+
+    /// Whether --fail-fast stopped the run at a failed file
+    fn failed_fast(&self) -> bool {
+        self.cli.fail_fast && self.progress.failure_count() > 0
+    }
+
+    /// Codec to decompress `file` with, when --decompress applies to it
+    fn codec(&self, file: &Path) -> Option<Codec> {
+        self.cli.decompress.then(|| Codec::for_path(file)).flatten()
+    }
+
+    /// Whether copy_file already adds to the byte counter while it streams `file`
+    fn counts_while_copying(&self, file: &Path) -> bool {
+        self.streamed.is_some()
+            || self.crypto.is_some()
+            || self.codec(file).is_some()
+            || !self.cli.also_to.is_empty()
+            || self.hashes_in_flight(file)
+            || (self.watchdog.abandons() && !self.cli.link && self.cli.symlink.is_none())
+    }
+
+    /// Whether `file` is hashed as it is copied, for --verify, --manifest and
+    /// --catalog; only plain copies write exactly the bytes they read
+    fn hashes_in_flight(&self, file: &Path) -> bool {
+        let cli = self.cli;
+        (cli.verify || cli.verify_sample.is_some() || cli.manifest.is_some() || self.catalog.is_some())
+            && self.crypto.is_none()
+            && self.codec(file).is_none()
+            && !cli.link
+            && cli.symlink.is_none()
+            && !cli.resume
+    }
+
+    /// Where `file` goes under each --also-to directory
+    fn extra_destinations(&self, file: &Path) -> Vec<PathBuf> {
+        self.cli
+            .also_to
+            .iter()
+            .map(|root| output_path(file, self.sources, root, self.crypto, self.cli.decompress))
+            .collect()
+    }
+
+    /// The first destination of `file` that would be written outside its tree
+    /// (`root`, or the --also-to directory), unless --allow-outside is given
+    fn outside_destination(&self, file: &Path, dest_path: &Path, root: &Path) -> Option<PathBuf> {
+        let cli = self.cli;
+        if cli.allow_outside {
+            return None;
+        }
+        // Only a plain fast copy writes through an existing symlink instead of replacing it
+        let follow_last = cli.fast_mode && !cli.link && cli.symlink.is_none() && !walk::is_dangling(file);
+        std::iter::once((dest_path.to_path_buf(), root))
+            .chain(self.extra_destinations(file).into_iter().zip(cli.also_to.iter().map(PathBuf::as_path)))
+            .find(|(path, root)| escapes(path, root, follow_last))
+            .map(|(path, _)| path)
+    }
+
+    /// With --update, whether `dest_path` already holds the current `file`
+    /// (described by `source`), by the sync state or else by being as large
+    /// and at least as new (or having the same content, with --checksum);
+    /// returns the size of the file skipped
+    fn up_to_date(&mut self, file: &Path, source: Option<&fs::Metadata>, dest_path: &Path) -> Option<u64> {
+        if !self.cli.update {
+            return None;
+        }
+        let source = source?;
+        let known = self.sync_state.as_ref().is_some_and(|state| state.unchanged(file, dest_path, source));
+        if !known && !self.holds_source(file, source, dest_path) {
+            return None;
+        }
+        if let Some(state) = &mut self.sync_state {
+            state.record(file, dest_path, source);
+        }
+        Some(source.len())
+    }
+
+    /// Whether `copy` is as large as `file` (described by `source`) and at
+    /// least as new, or has the same content with --checksum
+    fn holds_source(&self, file: &Path, source: &fs::Metadata, copy: &Path) -> bool {
+        let Ok(meta) = fs::metadata(copy) else {
+            return false;
+        };
+        if !meta.is_file() || meta.len() != source.len() {
+            return false;
+        }
+        if self.cli.checksum {
+            matches!((self.source_hash(file), hash_range(copy, 0, meta.len())), (Ok(source), Ok(copy)) if source == copy)
+        } else {
+            matches!((source.modified(), meta.modified()), (Ok(source), Ok(copy)) if copy >= source)
+        }
+    }
+
+    /// With --link-dest, the copy of `file` (described by `source`) in the
+    /// previous snapshot when it is still current, and the size of `file`
+    fn previous_copy(&self, file: &Path, source: Option<&fs::Metadata>, dest_path: &Path, root: &Path) -> Option<(PathBuf, u64)> {
+        let previous = self.cli.link_dest.as_ref()?.join(dest_path.strip_prefix(root).ok()?);
+        let source = source?;
+        self.holds_source(file, source, &previous).then_some((previous, source.len()))
+    }
+
+    /// BLAKE3 of the whole of `file`, from the hash cache when it has not
+    /// changed since an earlier run. Destinations are always read: their
+    /// timestamps are what --checksum does not trust.
+    fn source_hash(&self, file: &Path) -> io::Result<blake3::Hash> {
+        if let Some(hash) = self.hash_cache.as_ref().and_then(|cache| cache.get(file)) {
+            return Ok(hash);
+        }
+        let hash = hash_range(file, 0, fs::metadata(file)?.len())?;
+        if let Some(cache) = &self.hash_cache {
+            cache.insert(file, hash);
+        }
+        Ok(hash)
+    }
+
+    /// Copy `file` to `dest_path` and every --also-to destination from a single read
+    fn fan_out(&self, file: &Path, dest_path: &Path, hasher: Option<&mut blake3::Hasher>) -> io::Result<u64> {
+        let mut destinations = vec![dest_path.to_path_buf()];
+        destinations.extend(self.extra_destinations(file));
+        for destination in &destinations {
+            self.create_parent(destination)?;
+        }
+
+        let fast_mode = self.cli.fast_mode;
+        let targets: Vec<PathBuf> = if fast_mode {
+            destinations.clone()
+        } else {
+            destinations.iter().map(|d| temp_path(d)).collect()
+        };
+        let result = fanout::fan_out(file, &targets, &self.byte_counter, self.watchdog.cancel_flag(), hasher).and_then(|bytes| {
+            if !fast_mode {
+                for (target, destination) in targets.iter().zip(&destinations) {
+                    fs::rename(target, destination)?;
+                }
+            }
+            Ok(bytes)
+        });
+        if result.is_err() && !fast_mode {
+            for target in &targets {
+                let _ = fs::remove_file(target);
+            }
+        }
+        result
+    }
+
+    /// Copy `file` to `dest_path` with the mode selected on the command line,
+    /// feeding the data read to `hasher` when there is one
+    fn copy_file(&self, file: &Path, dest_path: &Path, mut hasher: Option<&mut blake3::Hasher>) -> io::Result<u64> {
+        let cli = self.cli;
+
+        if let Some(crypto) = self.crypto {
+            let target = if cli.fast_mode { dest_path.to_path_buf() } else { temp_path(dest_path) };
+            let written = crypto.transform(file, &target, &self.byte_counter, self.watchdog.cancel_flag())?;
+            if !cli.fast_mode {
+                fs::rename(&target, dest_path)?;
+            }
+            return Ok(written);
+        }
+
+        if let Some(codec) = self.codec(file) {
+            let target = if cli.fast_mode { dest_path.to_path_buf() } else { temp_path(dest_path) };
+            let written = compress::decompress_file(file, &target, codec, &self.byte_counter, self.watchdog.cancel_flag())?;
+            if !cli.fast_mode {
+                fs::rename(&target, dest_path)?;
+            }
+            return Ok(written);
+        }
+
+        if !cli.also_to.is_empty() {
+            return self.fan_out(file, dest_path, hasher);
+        }
+
+        // Use safe copy with temporary file (unless fast mode)
+        let Some(stream_range) = self.streamed else {
+            // A temporary file left by a run that was killed, or kept by --resume, goes on from where it stopped
+            let resume_from = match cli.fast_mode || cli.link || cli.symlink.is_some() {
+                true => 0,
+                false => leftover_prefix(file, &temp_path(dest_path), hasher.as_deref_mut())?,
+            };
+            return if let Some(kind) = cli.symlink {
+                symlink_file_with_temp(file, dest_path, kind)
+            } else if cli.link {
+                link_file_with_temp(file, dest_path)
+            } else if resume_from > 0 || hasher.is_some() || self.watchdog.abandons() {
+                // Through our own buffer so the data can be hashed on the way, the
+                // copy given up part way, and a leftover temporary file continued
+                let target = if cli.fast_mode { dest_path.to_path_buf() } else { temp_path(dest_path) };
+                if resume_from > 0 {
+                    self.progress.say(format!("{} {}",
+                        style(icon!("⏩ ", "Resuming:")).cyan().bold(),
+                        tr!("{file} from byte {offset}", file = style(file.display()).white(), offset = style(resume_from).cyan())
+                    ));
+                }
+                let reader = HashingReader { inner: open_range(file, resume_from, u64::MAX)?, hasher };
+                let bytes = stream_copy(reader, &target, resume_from, None, &self.byte_counter, self.watchdog.cancel_flag())?;
+                copy_permissions(file, &target)?;
+                if !cli.fast_mode {
+                    fs::rename(&target, dest_path)?;
+                }
+                Ok(bytes)
+            } else if cli.fast_mode {
+                noatime::copy(file, dest_path)
+            } else {
+                copy_file_with_temp(file, dest_path)
+            };
+        };
+
+        let target = if cli.fast_mode { dest_path.to_path_buf() } else { temp_path(dest_path) };
+        let checkpoint = if cli.resume && stream_range.1 > cli.chunk_size {
+            Some(Checkpoint::new(file, &target, stream_range, cli.chunk_size)?)
+        } else {
+            None
+        };
+
+        // Prefer the exact checkpoint; fall back to verifying whatever partial data exists
+        let resume_from = match checkpoint.as_ref().and_then(|c| c.resume_offset(&target)) {
+            Some(completed) => completed,
+            None if cli.resume => resumable_prefix(file, stream_range, &target)?,
+            None => 0,
+        };
+        if resume_from > 0 {
+            self.progress.say(format!("{} {}",
+                style(icon!("⏩ ", "Resuming:")).cyan().bold(),
+                tr!("{file} from byte {offset}", file = style(file.display()).white(), offset = style(resume_from).cyan())
+            ));
+        }
+
+        let (offset, length) = stream_range;
+        let result = open_range(file, offset + resume_from, length - resume_from)
+            .and_then(|reader| stream_copy(
+                HashingReader { inner: reader, hasher },
+                &target,
+                resume_from,
+                checkpoint.as_ref(),
+                &self.byte_counter,
+                self.watchdog.cancel_flag(),
+            ))
+            .and_then(|bytes| {
+            copy_permissions(file, &target)?;
+            if !cli.fast_mode {
+                fs::rename(&target, dest_path)?;
+            }
+            Ok(bytes)
+        });
+
+        // An interrupted checkpoint is kept for the next --resume run
+        let interrupted = matches!(&result, Err(e) if e.kind() == io::ErrorKind::Interrupted);
+        if let Some(checkpoint) = checkpoint.as_ref().filter(|_| !interrupted) {
+            checkpoint.remove();
+        }
+        result
+    }
+
+    /// Recreate the dangling symlink `file` at `dest_path` and every --also-to
+    /// destination, or fail it, as --broken-symlinks says
+    fn copy_dangling(&self, file: &Path, dest_path: &Path) -> io::Result<u64> {
+        if self.cli.broken_symlinks != BrokenLinks::Recreate {
+            let target = fs::read_link(file).unwrap_or_default();
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("broken symlink, {} does not exist", target.display()),
+            ));
+        }
+        for extra in self.extra_destinations(file) {
+            self.create_parent(&extra)?;
+            recreate_symlink(file, &extra)?;
+        }
+        recreate_symlink(file, dest_path)
+    }
+
+    /// Copy one file and report the outcome; `retry` marks files that already failed once
+    fn transfer(&mut self, file: &Path, metadata: Option<&fs::Metadata>, dest_path: &Path, retry: bool) -> Flow {
+        let cli = self.cli;
+        self.progress.set_current_file(file);
+        let started = Instant::now();
+        // Links only reach here when --broken-symlinks asks to recreate or fail them
+        if metadata.is_none() && walk::is_dangling(file) {
+            let result = self.copy_dangling(file, dest_path).map(|bytes| Transferred { bytes, hash: None, unstable: false });
+            return self.report(file, dest_path, retry, started.elapsed(), result);
+        }
+
+        let destinations: Vec<PathBuf> = std::iter::once(dest_path.to_path_buf()).chain(self.extra_destinations(file)).collect();
+        let targets = destinations.iter().flat_map(|destination| [temp_path(destination), destination.clone()]).collect();
+        self.watchdog.begin(targets);
+        // Partial --resume data is kept for the next run; --fast-mode writes in place
+        let temps = destinations.iter().filter(|_| !cli.fast_mode && !cli.resume).map(|destination| temp_path(destination));
+        if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+            in_flight.extend(temps);
+        }
+        // Read before the copy, so a source changed meanwhile is copied again next time
+        let source_meta = metadata.filter(|_| self.sync_state.is_some());
+        let mut hasher = self.hashes_in_flight(file).then(blake3::Hasher::new);
+        let mut result = self.copy_file(file, dest_path, hasher.as_mut());
+        for _ in 0..IN_USE_RETRIES {
+            match &result {
+                Err(e) if in_use(e) => {
+                    thread::sleep(IN_USE_DELAY);
+                    if let Some(hasher) = &mut hasher {
+                        hasher.reset();
+                    }
+                    result = self.copy_file(file, dest_path, hasher.as_mut());
+                }
+                _ => break,
+            }
+        }
+        self.watchdog.end();
+        if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+            in_flight.clear();
+        }
+        if let Some(reason) = self.watchdog.abandoned() {
+            // Stopped by the watchdog rather than by Ctrl+C: an ordinary failure
+            if matches!(&result, Err(e) if e.kind() == io::ErrorKind::Interrupted) && !self.interrupted.load(Ordering::Relaxed) {
+                result = Err(io::Error::new(io::ErrorKind::TimedOut, reason));
+            }
+        }
+        // Links share the source's inode, whose streams and attributes are already the source's
+        if !cli.link && cli.symlink.is_none() {
+            let ownership = preserve::Ownership {
+                users: cli.usermap.as_ref(),
+                groups: cli.groupmap.as_ref(),
+                fake_super: cli.fake_super,
+            };
+            for destination in std::iter::once(dest_path.to_path_buf()).chain(self.extra_destinations(file)) {
+                if cli.ads {
+                    result = result.and_then(|bytes| ads::copy_streams(file, &destination).map(|()| bytes));
+                }
+                if !cli.preserve.contains(&Preserve::Mode) {
+                    result = result.and_then(|bytes| preserve::reset_mode(&destination).map(|()| bytes));
+                }
+                result = result.and_then(|bytes| preserve::apply(&cli.preserve, file, metadata, &destination, ownership).map(|()| bytes));
+            }
+        }
+        // Copies that keep the source's time (clones, CopyFileEx, --preserve=finder) would look stale
+        if cli.touch {
+            let now = SystemTime::now();
+            for destination in std::iter::once(dest_path.to_path_buf()).chain(self.extra_destinations(file)) {
+                result = result.and_then(|bytes| preserve::set_modified(&destination, now).map(|()| bytes));
+            }
+        }
+        if cli.chmod.is_some() || cli.chown.is_some() {
+            let mode = cli.chmod.and_then(|modes| modes.file);
+            for destination in std::iter::once(dest_path.to_path_buf()).chain(self.extra_destinations(file)) {
+                result = result.and_then(|bytes| ownership::apply(&destination, mode, cli.chown).map(|()| bytes));
+            }
+        }
+        if let (Ok(_), Some(state), Some(meta)) = (&result, &mut self.sync_state, source_meta) {
+            state.record(file, dest_path, meta);
+        }
+        // Log files and databases written to as they are read leave a copy that
+        // is neither their old contents nor their new
+        let copies_data = !cli.link && cli.symlink.is_none();
+        let unstable = result.is_ok() && copies_data && metadata.is_some_and(|before| source_changed(file, before));
+        let hash = hasher.map(|h| h.finalize());
+        self.report(file, dest_path, retry, started.elapsed(), result.map(|bytes| Transferred { bytes, hash, unstable }))
+    }
+
+    /// Record and print the outcome of copying `file`, which took `elapsed`
+    fn report(
+        &mut self,
+        file: &Path,
+        dest_path: &Path,
+        retry: bool,
+        elapsed: Duration,
+        result: io::Result<Transferred>,
+    ) -> Flow {
+        let cli = self.cli;
+        match result {
+            // Not counted as copied, not verified, and not removed by copro mv
+            Ok(Transferred { bytes: bytes_copied, unstable: true, .. }) => {
+                self.progress.say(format!("{} {} ({})",
+                    style(icon!("⚠️ ", "Unstable:")).yellow().bold(),
+                    style(file.display()).white(),
+                    tr!("changed while being copied")
+                ));
+                self.progress.tally.unstable += 1;
+                if retry {
+                    self.progress.clear_failure();
+                }
+                if !self.counts_while_copying(file) {
+                    self.byte_counter.fetch_add(bytes_copied, Ordering::Relaxed);
+                }
+                let extras = self.extra_destinations(file);
+                for destination in std::iter::once(dest_path).chain(extras.iter().map(PathBuf::as_path)) {
+                    self.record(file, destination, bytes_copied, elapsed, catalog::Outcome::Unstable);
+                }
+                if let Some(manifest) = &mut self.manifest {
+                    manifest.entries.push(ManifestEntry::failed(file, dest_path, String::from("unstable: changed while being copied")));
+                }
+                self.unstable.push(FailedFile {
+                    source: file.to_path_buf(),
+                    destination: dest_path.to_path_buf(),
+                    error: String::from("changed while being copied"),
+                    cli,
+                });
+                Flow::Continue
+            }
+            Ok(Transferred { bytes: bytes_copied, hash, .. }) => {
+                if bytes_copied == 0 && file.exists() {
+                    self.progress.say(format!("{} {} ({})",
+                        style(icon!("⚠️ ", "Warning:")).yellow().bold(),
+                        style(file.display()).white(),
+                        tr!("0 bytes copied - file may be empty")
+                    ));
+                }
+                self.progress.increment();
+                if self.sources.renamed(file).is_some() {
+                    self.progress.tally.renamed += 1;
+                } else {
+                    self.progress.tally.copied += 1;
+                }
+                if retry {
+                    self.progress.clear_failure();
+                }
+                if !self.counts_while_copying(file) {
+                    self.byte_counter.fetch_add(bytes_copied, Ordering::Relaxed);
+                }
+                if cli.by_extension {
+                    let entry = self.extension_stats.entry(extension_key(file)).or_default();
+                    entry.0 += 1;
+                    entry.1 += bytes_copied;
+                }
+                if cli.verbose > 0 || cli.by_extension {
+                    self.timings.push((file.to_path_buf(), bytes_copied, elapsed));
+                }
+                let extras = self.extra_destinations(file);
+                if (cli.verify || cli.verify_sample.is_some()) && file.exists() {
+                    let range = self.streamed.unwrap_or((0, bytes_copied));
+                    for destination in std::iter::once(dest_path).chain(extras.iter().map(PathBuf::as_path)) {
+                        self.copied.push(verify::Copied {
+                            source: file.to_path_buf(),
+                            destination: destination.to_path_buf(),
+                            range,
+                            source_hash: hash,
+                        });
+                    }
+                }
+                // A symbolic link to the source would dangle once it is removed
+                let linked = || fs::symlink_metadata(dest_path).is_ok_and(|meta| meta.file_type().is_symlink())
+                    && !fs::symlink_metadata(file).is_ok_and(|meta| meta.file_type().is_symlink());
+                if cli.remove_sources && !linked() {
+                    self.moved.push(file.to_path_buf());
+                }
+                if let Some(print0) = &mut self.print0 {
+                    for destination in std::iter::once(dest_path).chain(extras.iter().map(PathBuf::as_path)) {
+                        let _ = print0.emit(destination);
+                    }
+                }
+                for destination in std::iter::once(dest_path).chain(extras.iter().map(PathBuf::as_path)) {
+                    self.record(file, destination, bytes_copied, elapsed, catalog::Outcome::Copied { hash });
+                }
+                if let Some(manifest) = &mut self.manifest {
+                    manifest.entries.push(ManifestEntry::copied(file, dest_path, bytes_copied, hash));
+                    for extra in extras {
+                        manifest.entries.push(ManifestEntry::copied(file, &extra, bytes_copied, hash));
+                    }
+                }
+                match (cli.verbose, &self.progress.templates.file) {
+                    (0, _) => {}
+                    (_, Some(template)) => self.progress.say(i18n::fill(template, &[
+                        ("file", file.display().to_string()),
+                        ("destination", dest_path.display().to_string()),
+                        ("size", format_size(bytes_copied as f64)),
+                        ("seconds", format!("{:.2}", elapsed.as_secs_f32())),
+                        ("rate", format_rate(bytes_copied as f64 / elapsed.as_secs_f64().max(0.001))),
+                    ])),
+                    (1, None) => self.progress.say(format!("{} {}",
+                        style(icon!("✅ ", "Success:")).green().bold(),
+                        style(file.display()).white()
+                    )),
+                    (_, None) => self.progress.say(format!("{} {} ({})",
+                        style(icon!("✅ ", "Success:")).green().bold(),
+                        style(file.display()).white(),
+                        tr!("{size} in {seconds}s, {rate}",
+                            size = style(format_size(bytes_copied as f64)).cyan(),
+                            seconds = format!("{:.2}", elapsed.as_secs_f32()),
+                            rate = format_rate(bytes_copied as f64 / elapsed.as_secs_f64().max(0.001))
+                        )
+                    )),
+                }
+                Flow::Continue
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted && self.interrupted.load(Ordering::Relaxed) => {
+                self.progress.interrupted(self.stop_reason());
+                if cli.resume {
+                    // Keep the partial data so the next --resume run can continue it
+                    self.progress.say(format!("{}{}", icon!("💡 "), tr!("Run the same command with --resume to continue where it stopped")));
+                } else if !cli.fast_mode {
+                    for destination in std::iter::once(dest_path.to_path_buf()).chain(self.extra_destinations(file)) {
+                        let _ = fs::remove_file(temp_path(&destination));
+                    }
+                }
+                Flow::Stop
+            }
+            // Deleted since the walk listed it: nothing to copy, and nothing wrong
+            Err(e) if e.kind() == io::ErrorKind::NotFound && fs::symlink_metadata(file).is_err() => {
+                self.progress.say(format!("{} {} ({})",
+                    style(icon!("❌ ", "Skipped:")).yellow().bold(),
+                    style(file.display()).white(),
+                    tr!("file no longer exists")
+                ));
+                self.progress.tally.skipped += 1;
+                if !cli.fast_mode {
+                    let _ = fs::remove_file(temp_path(dest_path));
+                }
+                if retry {
+                    self.progress.clear_failure();
+                }
+                Flow::Continue
+            }
+            Err(e) if in_use(&e) => {
+                // Open profile files (browsers, mail) are expected; not worth a failure
+                self.progress.say(format!("{} {} ({})",
+                    style(icon!("🔒 ", "Skipped:")).yellow().bold(),
+                    style(file.display()).white(),
+                    tr!("in use by another program")
+                ));
+                self.progress.tally.skipped += 1;
+                if !cli.fast_mode {
+                    let _ = fs::remove_file(temp_path(dest_path));
+                }
+                if retry {
+                    self.progress.clear_failure();
+                }
+                if let Some(manifest) = &mut self.manifest {
+                    manifest.entries.push(ManifestEntry::failed(file, dest_path, String::from("skipped: in use")));
+                }
+                let size = fs::metadata(file).map_or(0, |meta| meta.len());
+                self.record(file, dest_path, size, Duration::ZERO, catalog::Outcome::Skipped("in use"));
+                self.in_use.push(file.to_path_buf());
+                Flow::Continue
+            }
+            Err(e) => {
+                let failures = if retry {
+                    self.progress.failure_count()
+                } else {
+                    self.progress.record_failure()
+                };
+                self.progress.set_last_failure(file, &e);
+                self.progress.say(format!("{} {} ({})", 
+                    style(icon!("❌ ", "Failed:")).red().bold(),
+                    style(file.display()).white(),
+                    style(&e).red()
+                ));
+                
+                // Clean up any partial temporary files
+                if !cli.fast_mode || cli.link || cli.symlink.is_some() {
+                    let _ = fs::remove_file(temp_path(dest_path));
+                }
+                self.failed.push(FailedFile {
+                    source: file.to_path_buf(),
+                    destination: dest_path.to_path_buf(),
+                    error: e.to_string(),
+                    cli,
+                });
+
+                // A partial copy is worse than none for some pipelines
+                if cli.fail_fast {
+                    let _ = self.stop_reason.set(StopReason::FailFast);
+                    self.progress.interrupted(StopReason::FailFast);
+                    return Flow::Stop;
+                }
+                // Hundreds of failures in a row usually mean the destination went away
+                if let Some(limit) = cli.max_errors.filter(|&limit| failures > limit) {
+                    let _ = self.stop_reason.set(StopReason::MaxErrors(limit));
+                    self.progress.interrupted(StopReason::MaxErrors(limit));
+                    return Flow::Stop;
+                }
+                Flow::Continue
+            }
+        }
+    }
+
+    /// With -vvv, say how `file` is about to be written to `dest_path` and why
+    fn explain(&self, file: &Path, dest_path: &Path) {
+        let cli = self.cli;
+        let exists = fs::symlink_metadata(dest_path).is_ok();
+        let mut reasons = vec![if exists { "overwrites the existing file" } else { "new file" }];
+        if exists && self.undo.is_some() {
+            reasons.push("old version kept for copro undo");
+        }
+        if exists && cli.backups.is_some_and(|keep| keep > 0) {
+            reasons.push("previous version kept as .~1~");
+        }
+        if self.sources.renamed(file).is_some() {
+            reasons.push("renamed, another source has the same path (--merge keep-both)");
+        }
+        match self.crypto {
+            Some(Crypto::Encrypt(_)) => reasons.push("encrypted, .age added to the name"),
+            Some(Crypto::Decrypt(_)) => reasons.push("decrypted, .age dropped from the name"),
+            None => {}
+        }
+        if self.codec(file).is_some() {
+            reasons.push("decompressed, extension dropped");
+        }
+        reasons.push(if walk::is_dangling(file) {
+            "broken symlink recreated"
+        } else if cli.link {
+            "hard link"
+        } else if cli.symlink.is_some() {
+            "symlink to the source"
+        } else if cli.fast_mode {
+            "written in place (--fast-mode)"
+        } else {
+            "written to a temporary file, then renamed"
+        });
+        self.progress.say(format!("{} {} → {} ({})",
+            style(icon!("🧭 ", "Decision:")).cyan(),
+            style(file.display()).white(),
+            dest_path.display(),
+            reasons.into_iter().map(i18n::text).collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    /// Copy every pending file, then give failures the configured retry
+    /// passes; returns false when the run was stopped part way
+    fn copy_all(
+        &mut self,
+        pending: impl Iterator<Item = io::Result<Pending<'a>>>,
+        interrupt_rx: &mpsc::Receiver<()>,
+    ) -> io::Result<bool> {
+        let retry_passes = self.cli.retry_passes;
+        let settle_passes = self.cli.settle;
+        for item in pending {
+            let Pending { file, metadata, destination: dest_path, root, cli } = item?;
+            self.cli = cli;
+
+            // Check for interruption before each file
+            if self.interrupted.load(Ordering::Relaxed) {
+                self.progress.interrupted(self.stop_reason());
+                return Ok(false);
+            }
+
+            // Check for interruption signal (non-blocking)
+            if interrupt_rx.try_recv().is_ok() {
+                self.progress.interrupted(self.stop_reason());
+                return Ok(false);
+            }
+
+            if let Some(outside) = self.outside_destination(&file, &dest_path, root) {
+                self.progress.say(format!("{} {} ({})",
+                    style(icon!("🚫 ", "Refused:")).red().bold(),
+                    style(file.display()).white(),
+                    tr!("would be written to {path}, outside the destination; --allow-outside permits it",
+                        path = style(outside.display()).yellow()
+                    )
+                ));
+                if let Some(manifest) = &mut self.manifest {
+                    manifest.entries.push(ManifestEntry::failed(&file, &dest_path, String::from("refused: outside the destination")));
+                }
+                let size = metadata.as_ref().map_or(0, fs::Metadata::len);
+                self.record(&file, &dest_path, size, Duration::ZERO, catalog::Outcome::Skipped("refused: outside the destination"));
+                self.progress.tally.skipped += 1;
+                continue;
+            }
+
+            if let Some(size) = self.up_to_date(&file, metadata.as_ref(), &dest_path) {
+                if cli.verbose >= 2 {
+                    self.progress.say(format!("{} {} ({})",
+                        style(icon!("⏭️  ", "Skipped:")).dim(),
+                        style(file.display()).white(),
+                        tr!("up to date")
+                    ));
+                }
+                self.progress.increment();
+                self.progress.tally.unchanged += 1;
+                self.byte_counter.fetch_add(size, Ordering::Relaxed);
+                continue;
+            }
+
+            let extras = self.extra_destinations(&file);
+            if let Some(undo) = &mut self.undo {
+                let in_place = cli.fast_mode && !cli.link && cli.symlink.is_none();
+                for path in std::iter::once(&dest_path).chain(&extras) {
+                    undo.before_write(path, in_place)?;
+                }
+            }
+
+            self.create_parent(&dest_path)?;
+            if cli.chmod.is_some() || cli.chown.is_some() {
+                let roots = std::iter::once(root).chain(cli.also_to.iter().map(PathBuf::as_path));
+                for (path, root) in std::iter::once(&dest_path).chain(&extras).zip(roots) {
+                    self.attribute_directories(path, root)?;
+                }
+            }
+
+            // Copying a file onto itself (e.g. through a symlink) would truncate it
+            if is_same_file(&file, metadata.as_ref(), &dest_path) {
+                if cli.link || cli.symlink.is_some() {
+                    // Already linked by a previous run
+                    if cli.verbose >= 2 {
+                        self.progress.say(format!("{} {} ({})",
+                            style(icon!("⏭️  ", "Skipped:")).dim(),
+                            style(file.display()).white(),
+                            tr!("already linked to the destination")
+                        ));
+                    }
+                    self.progress.increment();
+                    self.progress.tally.unchanged += 1;
+                    continue;
+                }
+                self.progress.say(format!("{} {} ({})",
+                    style(icon!("❌ ", "Skipped:")).yellow().bold(),
+                    style(file.display()).white(),
+                    tr!("destination is the same file")
+                ));
+                self.progress.tally.skipped += 1;
+                continue;
+            }
+
+            if let Some(keep) = cli.backups {
+                let in_place = cli.fast_mode && !cli.link && cli.symlink.is_none();
+                let rotated = std::iter::once(&dest_path)
+                    .chain(&extras)
+                    .try_for_each(|path| versions::rotate(path, keep, in_place))
+                    .map_err(|e| io::Error::new(e.kind(), format!("could not keep the previous version: {e}")));
+                if let Err(e) = rotated {
+                    if let Flow::Stop = self.report(&file, &dest_path, false, Duration::ZERO, Err(e)) {
+                        return Ok(false);
+                    }
+                    continue;
+                }
+            }
+
+            if let Some((previous, size)) = self.previous_copy(&file, metadata.as_ref(), &dest_path, root) {
+                let linked = match fs::remove_file(&dest_path) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                    _ => fs::hard_link(&previous, &dest_path),
+                };
+                match linked {
+                    Ok(()) => {
+                        if cli.verbose >= 2 {
+                            self.progress.say(format!("{} {} ({})",
+                                style(icon!("🔗 ", "Linked:")).dim(),
+                                style(file.display()).white(),
+                                tr!("unchanged since {path}", path = style(previous.display()).dim())
+                            ));
+                        }
+                        self.progress.increment();
+                        self.progress.tally.unchanged += 1;
+                        self.byte_counter.fetch_add(size, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        let e = io::Error::new(e.kind(), format!("could not link to the previous snapshot: {e}"));
+                        if let Flow::Stop = self.report(&file, &dest_path, false, Duration::ZERO, Err(e)) {
+                            return Ok(false);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            if cli.verbose >= 3 {
+                self.explain(&file, &dest_path);
+            }
+            if let Flow::Stop = self.transfer(&file, metadata.as_ref(), &dest_path, false) {
+                return Ok(false);
+            }
+
+            // Pace the run for recordings and demos; never slows a real copy unasked
+            if let Some(delay) = cli.demo_delay {
+                thread::sleep(Duration::from_millis(delay));
+            }
+        }
+        // Ctrl+C during the last file lets it finish, but the run still stopped
+        if self.interrupted.load(Ordering::Relaxed) {
+            self.progress.interrupted(self.stop_reason());
+            return Ok(false);
+        }
+
+        // Files that changed while they were read get another copy, once they may have settled
+        for pass in 1..=settle_passes {
+            if self.unstable.is_empty() {
+                break;
+            }
+            let pending = std::mem::take(&mut self.unstable);
+            self.progress.tally.unstable -= pending.len();
+            self.progress.say(format!("{} {}",
+                style(icon!("⏳ ", "SETTLE:")).cyan().bold(),
+                tr!("Settle pass {pass}/{passes}: {count} files changed while being copied",
+                    pass = pass,
+                    passes = settle_passes,
+                    count = style(pending.len()).yellow()
+                )
+            ));
+            thread::sleep(Duration::from_secs(1));
+
+            for unstable in pending {
+                if self.interrupted.load(Ordering::Relaxed) {
+                    self.progress.interrupted(self.stop_reason());
+                    return Ok(false);
+                }
+                self.cli = unstable.cli;
+                // This time the copy is checked against the source as it is now
+                let metadata = fs::metadata(&unstable.source).ok();
+                if let Flow::Stop = self.transfer(&unstable.source, metadata.as_ref(), &unstable.destination, false) {
+                    return Ok(false);
+                }
+            }
+        }
+
+        // Give failed files another chance; most network-filesystem errors are transient
+        for pass in 1..=retry_passes {
+            if self.failed.is_empty() {
+                break;
+            }
+            let pending = std::mem::take(&mut self.failed);
+            // A directory removed meanwhile may be why a file failed; check afresh
+            self.created_dirs.borrow_mut().clear();
+            self.progress.say(format!("{} {}",
+                style(icon!("🔁 ", "RETRY:")).cyan().bold(),
+                tr!("Retry pass {pass}/{passes}: {count} failed files",
+                    pass = pass,
+                    passes = retry_passes,
+                    count = style(pending.len()).yellow()
+                )
+            ));
+            thread::sleep(Duration::from_secs(1));
+
+            for failed in pending {
+                if self.interrupted.load(Ordering::Relaxed) {
+                    self.progress.interrupted(self.stop_reason());
+                    return Ok(false);
+                }
+                self.cli = failed.cli;
+                // Read afresh: the file may have changed (or gone) since it failed
+                let metadata = fs::metadata(&failed.source).ok();
+                if let Flow::Stop = self.transfer(&failed.source, metadata.as_ref(), &failed.destination, true) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Wrap up the run: the summary, verification, what was left out and the manifest
+    fn conclude(mut self, completed: bool, walk_notes: &walk::Notes) {
+        let cli = self.cli;
+        if completed {
+            self.progress.finish();
+            if cli.by_extension && !self.extension_stats.is_empty() {
+                print_extension_summary(&self.extension_stats);
+            }
+            print_slow_files(&self.timings);
+        }
+
+        // A sample checks a random share of the copies, and every large one
+        if let Some(percent) = cli.verify_sample.filter(|_| completed) {
+            let sampled = self.copied.len();
+            self.copied.retain(|c| c.range.1 >= cli.verify_above || fastrand::f64() * 100.0 < percent);
+            println!("{}{}", icon!("🎲 "), tr!("Verifying a sample of {count} of {total} copies", count = style(self.copied.len()).cyan(), total = sampled));
+        }
+
+        // Read everything back so bad media or a flaky link cannot pass unnoticed
+        if completed && !self.copied.is_empty() {
+            match verify_copies(&self.copied, self.hash_cache.as_ref(), cli.verify_threads, &self.interrupted) {
+                Some(mismatches) if mismatches.is_empty() => println!("{}{} {}", icon!("🔍 "),
+                    style(tr!("VERIFIED!")).green().bold(),
+                    tr!("{count} copies match their source", count = style(self.copied.len()).cyan().bold())
+                ),
+                Some(mismatches) => {
+                    for (destination, mismatch) in &mismatches {
+                        println!("{} {} ({})",
+                            style(icon!("❌ ", "Mismatch:")).red().bold(),
+                            style(destination.display()).white(),
+                            style(mismatch.describe()).red()
+                        );
+                        if let Some(manifest) = &mut self.manifest {
+                            manifest.mark_failed(destination, mismatch.describe());
+                        }
+                        if let Some(state) = &mut self.sync_state {
+                            state.forget(destination);
+                        }
+                        if let Some(copy) = self.copied.iter().find(|copy| copy.destination == *destination) {
+                            self.moved.retain(|source| *source != copy.source);
+                        }
+                    }
+                    println!("{}{} {}", icon!("⚠️  "),
+                        style(tr!("VERIFY FAILED!")).red().bold(),
+                        tr!("{count} of {total} copies differ from their source",
+                            count = style(mismatches.len()).red().bold(),
+                            total = style(self.copied.len()).cyan()
+                        )
+                    );
+                }
+                None => println!("{}{} {}", icon!("🛑 "),
+                    style(tr!("INTERRUPTED!")).red().bold(),
+                    tr!("Verification stopped before every copy was checked")
+                ),
+            }
+        }
+        let saved = match (&cli.sync_state, self.sync_state.take()) {
+            (Some(path), Some(state)) => state.save(path, completed).map_err(|e| (path, e)),
+            _ => Ok(()),
+        };
+        if let Err((path, e)) = saved {
+            println!("{} {}",
+                style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+                tr!("Could not save the sync state {path}: {error}", path = path.display(), error = e)
+            );
+        }
+        if let Err(e) = self.hash_cache.as_ref().map_or(Ok(()), hashcache::HashCache::save) {
+            println!("{} {}", style(icon!("⚠️ ", "WARNING:")).yellow().bold(), tr!("Could not save the hash cache: {error}", error = e));
+        }
+
+        print_walk_notes(walk_notes);
+        if !self.in_use.is_empty() {
+            println!("{} {}",
+                style(icon!("🔒 ", "SKIPPED:")).yellow().bold(),
+                tr!("{count} files were in use by another program and not copied:", count = style(self.in_use.len()).yellow())
+            );
+            for file in &self.in_use {
+                println!("   {}", file.display());
+            }
+        }
+        if !self.unstable.is_empty() {
+            println!("{} {}",
+                style(icon!("⚠️ ", "UNSTABLE:")).yellow().bold(),
+                tr!("{count} files changed while being copied; their copies may be inconsistent:", count = style(self.unstable.len()).yellow())
+            );
+            for unstable in &self.unstable {
+                println!("   {}", unstable.source.display());
+            }
+            if cli.settle == 0 {
+                println!("{}{}", icon!("💡 "), tr!("--settle copies them again once the rest is done"));
+            }
+        }
+
+        self.remove_moved();
+
+        // What the run changed is on disk before copro exits, however it ended
+        if let Some(Err(e)) = self.undo.as_ref().map(undo::UndoLog::sync) {
+            println!("{} {}", style(icon!("⚠️ ", "WARNING:")).yellow().bold(), tr!("Could not save the undo log: {error}", error = e));
+        }
+
+        // Files still failed after the retries
+        let failures: Vec<_> = self.failed.iter().map(|f| (f.source.clone(), f.destination.clone(), f.error.clone())).collect();
+        for (source, destination, error) in &failures {
+            let size = fs::metadata(source).map_or(0, |meta| meta.len());
+            self.record(source, destination, size, Duration::ZERO, catalog::Outcome::Failed(error));
+        }
+        let status = match self.stop_reason() {
+            _ if completed => "completed",
+            StopReason::Interrupted => "interrupted",
+            StopReason::Timeout => "timeout",
+            StopReason::MaxErrors(_) => "max-errors",
+            StopReason::FailFast => "fail-fast",
+        };
+        if let Some(report) = self.csv_report.take() {
+            let path = report.path.clone();
+            match report.finish() {
+                Ok(()) => println!("{}{}", icon!("🧾 "), tr!("CSV report written to {path}", path = style(path.display()).white())),
+                Err(e) => eprintln!("{} {}",
+                    style(icon!("❌ ", "ERROR:")).red().bold(),
+                    tr!("Could not write the CSV report {path}: {error}", path = style(path.display()).white(), error = e)
+                ),
+            }
+        }
+        if let Some(report) = self.html_report.take() {
+            let path = report.path.clone();
+            match report.finish(status) {
+                Ok(()) => println!("{}{}", icon!("🧾 "), tr!("HTML report written to {path}", path = style(path.display()).white())),
+                Err(e) => eprintln!("{} {}",
+                    style(icon!("❌ ", "ERROR:")).red().bold(),
+                    tr!("Could not write the HTML report {path}: {error}", path = style(path.display()).white(), error = e)
+                ),
+            }
+        }
+        if let Some(catalog) = self.catalog.take() {
+            let summary = catalog::Summary {
+                status,
+                copied: self.progress.tally.copied + self.progress.tally.renamed,
+                failed: self.failed.len(),
+                bytes: self.byte_counter.load(Ordering::Relaxed),
+            };
+            self.note_catalog_error(catalog.finish(summary));
+        }
+
+        // The manifest is written even for stopped runs, covering what was done
+        if let (Some(path), Some(mut manifest)) = (&cli.manifest, self.manifest.take()) {
+            for failed in &self.failed {
+                manifest.entries.push(ManifestEntry::failed(&failed.source, &failed.destination, failed.error.clone()));
+            }
+            match manifest.save(path) {
+                Ok(()) => println!("{}{}", icon!("🧾 "), tr!("Manifest written to {path}", path = style(path.display()).white())),
+                Err(e) => eprintln!("{} {}",
+                    style(icon!("❌ ", "ERROR:")).red().bold(),
+                    tr!("Could not write manifest {path}: {error}", path = style(path.display()).white(), error = e)
+                ),
+            }
+        }
+    }
+}
+
+/// Remove the directories in `dirs`, which files were moved out of, and
+/// their parents up to the source root, wherever that left them empty.
+/// Empty directories that held nothing this run moved are left alone.
+fn remove_emptied_dirs(dirs: HashSet<PathBuf>, roots: &[PathBuf]) {
+    let mut dirs: Vec<PathBuf> = dirs.into_iter().collect();
+    // Deepest first, so a parent is only tried once its children are gone
+    dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    for dir in &dirs {
+        for dir in dir.ancestors() {
+            let in_source = roots.iter().any(|root| root.is_dir() && dir.starts_with(root));
+            // remove_dir only takes empty directories
+            if !in_source || fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Lock the directory each copy in `targets` (source, destination) writes
+/// into, once each; none, with the error printed, when another run holds one
+fn lock_destinations(targets: &[(&Path, &Path)]) -> Option<Vec<lock::DestinationLock>> {
+    let mut seen = HashSet::new();
+    let mut locks = Vec::new();
+    for &(source, destination) in targets {
+        // A single file lands in the directory that holds its destination
+        let dir = match source.is_dir() || destination.is_dir() {
+            true => destination,
+            false => destination.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new(".")),
+        };
+        if !seen.insert(resolve_path(dir)) {
+            continue;
+        }
+        match lock::DestinationLock::acquire(dir) {
+            Ok(lock) => locks.push(lock),
+            Err(lock::LockError::Busy(pid)) => {
+                let path = style(dir.display()).white();
+                eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), match pid {
+                    Some(pid) => tr!("Another copro run is already in progress in {path} (process {pid})", path = path, pid = pid),
+                    None => tr!("Another copro run is already in progress in {path}", path = path),
+                });
+                return None;
+            }
+            Err(lock::LockError::Io(e)) => eprintln!("{} {}",
+                style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+                tr!("Could not lock {path}, another run could write there at the same time: {error}", path = dir.join(lock::NAME).display(), error = e)
+            ),
+        }
+    }
+    Some(locks)
+}
+
+/// --sync-fs and --eject: flush the filesystems of `destinations` to their
+/// devices, then, if the run finished, take out the drives they are on
+fn finish_media(cli: &CopyArgs, destinations: &[&Path], completed: bool) {
+    if !cli.sync_fs && !cli.eject {
+        return;
+    }
+    let warn = |message: String| eprintln!("{} {}", style(icon!("⚠️ ", "WARNING:")).yellow().bold(), message);
+    for destination in destinations {
+        let started = Instant::now();
+        match media::sync_filesystem(destination) {
+            Ok(()) => println!("{}{}", icon!("💾 "),
+                tr!("Flushed {path} to its device in {seconds}s",
+                    path = style(destination.display()).white(),
+                    seconds = format!("{:.2}", started.elapsed().as_secs_f32())
+                )
+            ),
+            Err(e) => warn(tr!("Could not flush {path} to its device: {error}", path = destination.display(), error = e)),
+        }
+    }
+    if !cli.eject {
+        return;
+    }
+    if !completed {
+        println!("{}{}", icon!("💡 "), tr!("Not ejecting: the run did not finish, so the drive stays mounted to resume it"));
+        return;
+    }
+
+    // Several destinations may be on one drive, which is ejected once
+    let mut ejected = HashSet::new();
+    for destination in destinations {
+        let volume = match media::volume(destination) {
+            Ok(volume) => volume,
+            Err(e) => {
+                warn(tr!("Could not eject the drive holding {path}: {error}", path = destination.display(), error = e));
+                continue;
+            }
+        };
+        if !ejected.insert(volume.device.clone()) {
+            continue;
+        }
+        match media::eject(&volume) {
+            Ok(()) => println!("{}{} {}", icon!("⏏️  "),
+                style(tr!("EJECTED!")).green().bold(),
+                tr!("{device} can be removed safely", device = style(volume.device.display()).white())
+            ),
+            Err(e) => warn(tr!("Could not eject {device}: {error}", device = volume.device.display(), error = e)),
+        }
+    }
+}
+
+/// Ask for a path interactively, offering the recently used ones
+#[cfg(feature = "prompts")]
+fn ask_path(prompt: &str, kind: recent::Kind) -> Result<PathBuf> {
+    picker::choose_path(prompt, &recent::load(kind))
+}
+
+#[cfg(not(feature = "prompts"))]
+fn ask_path(_prompt: &str, kind: recent::Kind) -> Result<PathBuf> {
+    let what = match kind {
+        recent::Kind::Source => "source",
+        recent::Kind::Destination => "destination",
+    };
+    anyhow::bail!("no {what} path given (this build has no interactive prompts)")
+}
+
+/// What the run does to files already in the destination, for the summary before it starts
+fn existing_files_policy(cli: &CopyArgs) -> String {
+    let policy = if cli.update && cli.checksum {
+        tr!("skip those with the same content, overwrite the rest")
+    } else if cli.update {
+        tr!("skip those up to date (same size, not older), overwrite the rest")
+    } else {
+        tr!("overwrite")
+    };
+    match cli.backups.filter(|&keep| keep > 0) {
+        Some(keep) => format!("{policy} {}", tr!("(keeping {count} previous versions)", count = keep)),
+        None => policy.to_string(),
+    }
+}
+
+/// Begin recording the run for `copro undo`, unless --no-undo; the copy goes
+/// ahead without it when the log cannot be written
+fn start_undo_log(cli: &CopyArgs) -> Option<undo::UndoLog> {
+    if cli.no_undo {
+        undo::discard();
+        return None;
+    }
+    undo::UndoLog::start(cli.undo_copies)
+        .inspect_err(|e| println!("{} {}",
+            style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+            tr!("Undo log unavailable, `copro undo` will not cover this run: {error}", error = e)
+        ))
+        .ok()
+}
+
+/// Start recording the run in the --catalog; the copy goes ahead without it
+/// when the catalog cannot be opened
+fn open_catalog(cli: &CopyArgs, source: &Path, destination: &Path) -> Option<catalog::Catalog> {
+    let path = cli.catalog.as_ref()?.clone().or_else(catalog::default_path);
+    path.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory (HOME is not set)"))
+        .and_then(|path| catalog::Catalog::start(&path, source, destination))
+        .inspect_err(|e| println!("{} {}",
+            style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+            tr!("Run catalog unavailable, this run will not be recorded: {error}", error = e)
+        ))
+        .ok()
+}
+
+/// Create the --report-csv file; the copy goes ahead without it when it
+/// cannot be created
+fn create_csv_report(cli: &CopyArgs) -> Option<report::CsvReport> {
+    let path = cli.report_csv.as_ref()?;
+    report::CsvReport::create(path)
+        .inspect_err(|e| println!("{} {}",
+            style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+            tr!("Could not write the CSV report {path}: {error}", path = path.display(), error = e)
+        ))
+        .ok()
+}
+
+/// What the previous --update run recorded with --sync-state; `Err` once a
+/// state that cannot be read has been reported
+fn load_sync_state(cli: &CopyArgs) -> Result<Option<syncstate::SyncState>, ()> {
+    let Some(path) = &cli.sync_state else {
+        return Ok(None);
+    };
+    syncstate::SyncState::load(path).map(Some).map_err(|e| {
+        eprintln!("{} {}",
+            style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("Could not read the sync state {path}: {error}", path = path.display(), error = e)
+        );
+    })
+}
+
+/// The hash cache, when this run hashes files and may use it
+fn open_hash_cache(cli: &CopyArgs) -> Option<hashcache::HashCache> {
+    ((cli.verify || cli.verify_sample.is_some() || cli.checksum) && !cli.no_hash_cache).then(hashcache::HashCache::load)
+}
+
+/// The user's output templates; the built-in lines when they cannot be read
+fn load_templates() -> templates::Templates {
+    templates::Templates::load().unwrap_or_else(|e| {
+        println!("{} {}",
+            style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+            tr!("Could not read the output templates {path}: {error}",
+                path = templates::templates_file().unwrap_or_default().display(),
+                error = e
+            )
+        );
+        templates::Templates::default()
+    })
+}
+
+/// Watch for stalled and overlong copies, showing stalls in `progress`
+fn start_watchdog(cli: &CopyArgs, progress: &mut AnimatedProgress, interrupted: &Arc<AtomicBool>) -> watchdog::Watchdog {
+    let limits = watchdog::Limits {
+        stall: cli.stall_after,
+        abandon_stalled: cli.abandon_stalled,
+        file_timeout: cli.file_timeout,
+    };
+    let watchdog = watchdog::Watchdog::start(limits, progress.byte_counter(), Arc::clone(interrupted));
+    progress.stall = Some(watchdog.stall());
+    watchdog
+}
+
+/// With --serve-progress, answer HTTP requests for the state of the run
+fn serve_progress(cli: &CopyArgs, progress: &AnimatedProgress) {
+    let Some(address) = cli.serve_progress else {
+        return;
+    };
+    match serve::serve(address, progress.status()) {
+        Ok(()) => progress.say(format!("{}{}", icon!("📡 "), tr!("Serving progress on http://{address}/", address = address))),
+        Err(e) => progress.say(format!("{} {}",
+            style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+            tr!("Could not serve progress on {address}: {error}", address = address, error = e)
+        )),
+    }
+}
+
+#[cfg(feature = "prompts")]
+fn choose_files(files: Vec<walk::FileEntry>, sources: &Sources) -> Result<Option<Vec<walk::FileEntry>>> {
+    if !console::user_attended_stderr() {
+        anyhow::bail!("--select needs an interactive terminal");
+    }
+    select::choose(files, |file| {
+        let root = sources.root_of(file);
+        match (sources.renamed(file), file.strip_prefix(root)) {
+            (Some(relative), _) => relative.to_path_buf(),
+            (None, Ok(relative)) if !relative.as_os_str().is_empty() => relative.to_path_buf(),
+            // A single source file
+            _ => PathBuf::from(file.file_name().unwrap_or(file.as_os_str())),
+        }
+    })
+}
+
+#[cfg(not(feature = "prompts"))]
+fn choose_files(_files: Vec<walk::FileEntry>, _sources: &Sources) -> Result<Option<Vec<walk::FileEntry>>> {
+    anyhow::bail!("--select is not available (this build has no interactive prompts)")
+}
+
+/// Stop through the same path as Ctrl+C once the time budget is spent
+fn stop_after(timeout: Option<Duration>, interrupted: &Arc<AtomicBool>) -> Arc<OnceLock<StopReason>> {
+    let stop_reason: Arc<OnceLock<StopReason>> = Arc::new(OnceLock::new());
+    if let Some(timeout) = timeout {
+        let stop_reason = Arc::clone(&stop_reason);
+        let interrupted = Arc::clone(interrupted);
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            let _ = stop_reason.set(StopReason::Timeout);
+            interrupted.store(true, Ordering::Relaxed);
+        });
+    }
+    stop_reason
+}
+
+/// Why a job from --jobs-from cannot run, if it cannot
+fn job_problem(job: &jobs::Job) -> Option<String> {
+    let options = &job.cli;
+    let resolved_source = resolve_path(&job.source);
+    let resolved_destination = resolve_path(&job.destination);
+    if !job.source.exists() {
+        Some(String::from(tr!("source does not exist")))
+    } else if resolved_source == resolved_destination || same_file(&job.source, &job.destination) {
+        Some(String::from(tr!("source and destination are the same")))
+    } else if job.source.is_dir() && resolved_destination.starts_with(&resolved_source) && !options.force {
+        Some(String::from(tr!("destination is inside the source tree (use --force to copy anyway)")))
+    } else if options.link && options.symlink.is_some() {
+        Some(String::from(tr!("link and symlink cannot be combined")))
+    } else if options.verify && (options.link || options.symlink.is_some()) {
+        Some(String::from(tr!("verify cannot be combined with link or symlink")))
+    } else if options.link && !same_filesystem(&resolved_source, &resolved_destination) {
+        Some(String::from(tr!("link requires source and destination on the same filesystem")))
+    } else {
+        options.preserve.iter().find_map(|p| p.unsupported()).map(i18n::text).map(String::from)
+    }
+}
+
+/// Run the copy jobs of --jobs-from one after the other under a single progress display
+fn run_jobs(
+    cli: &CopyArgs,
+    path: &Path,
+    print0: Option<print0::Print0>,
+    interrupt_rx: &mpsc::Receiver<()>,
+    interrupted: &Arc<AtomicBool>,
+) -> Result<()> {
+    let jobs = match jobs::load(path, cli) {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            eprintln!("{} {}",
+                style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("Could not read jobs from {path}: {error}", path = style(path.display()).white(), error = e)
+            );
             return Ok(());
         }
+    };
+    for job in &jobs {
+        if let Some(problem) = job_problem(job) {
+            eprintln!("{} {} {} → {}: {}",
+                style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("Job"),
+                style(job.source.display()).white(),
+                style(job.destination.display()).white(),
+                problem
+            );
+            return Ok(());
+        }
+    }
+
+    // Every job is listed up front so the progress covers them all
+    let spinner = ScanSpinner::start();
+    let walk_notes = walk::Notes::default();
+    let mut pending = Vec::new();
+    let mut total_bytes = 0;
+    for job in &jobs {
+        let resolved_destination = resolve_path(&job.destination);
+        let skip_dir = (job.source.is_dir() && resolved_destination.starts_with(resolve_path(&job.source)))
+            .then_some(resolved_destination);
+        for file in walk::Walker::new(&job.source, skip_dir.as_deref(), cli.broken_symlinks, &walk_notes) {
+            let file = file?;
+            spinner.found(file.size());
+            total_bytes += file.size();
+            let destination = destination_for(&file.path, &job.source, &job.destination);
+            pending.push(Pending { file: file.path, metadata: file.metadata, destination, root: &job.destination, cli: &job.cli });
+        }
+    }
+    spinner.finish();
+
+    println!("{}{} {}", icon!("🚀 "),
+        style(tr!("INITIALIZING")).cyan().bold(),
+        tr!("Running {count} jobs from {path}...", count = style(jobs.len()).yellow().bold(), path = style(path.display()).white())
+    );
+    println!("{}{} {}", icon!("📁 "), tr!("Files to copy:"), style(pending.len()).yellow().bold());
+    println!("{}{} {}", icon!("💾 "), tr!("Total size:"), style(format_size(total_bytes as f64)).green().bold());
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    // Held until the jobs are done, so a second run into one of their destinations stops here
+    let targets: Vec<(&Path, &Path)> = jobs
+        .iter()
+        .flat_map(|job| std::iter::once(&job.destination).chain(&job.cli.also_to).map(|destination| (job.source.as_path(), destination.as_path())))
+        .collect();
+    let Some(locks) = lock_destinations(&targets) else {
+        return Ok(());
+    };
+    let _sleep_inhibitor = (!cli.allow_sleep).then(inhibit::SleepInhibitor::acquire);
+    let Ok(sync_state) = load_sync_state(cli) else {
+        return Ok(());
+    };
+    let undo = start_undo_log(cli);
+    println!("{}{}", icon!("💡 "), tr!("Press Ctrl+C to safely stop the operation"));
+    println!();
+
+    let mut progress = AnimatedProgress::new(pending.len(), total_bytes);
+    let byte_counter = progress.byte_counter();
+    progress.terminal_title = cli.title;
+    progress.osc_progress = cli.osc_progress || cli.taskbar;
+    progress.altscreen = cli.altscreen;
+    progress.taskbar_progress = cli.taskbar;
+    progress.bell = cli.bell;
+    progress.templates = load_templates();
+    let watchdog = start_watchdog(cli, &mut progress, interrupted);
+    progress.start_animation(cli.low_animation);
+    status::listen(progress.status());
+    serve_progress(cli, &progress);
+
+    // Each job records its own paths; the header names the first one
+    let manifest = cli.manifest.is_some().then(|| Manifest::new(&jobs[0].source, &jobs[0].destination));
+    let sources = Sources::single(jobs[0].source.clone());
+    let mut run = Run {
+        cli,
+        sources: &sources,
+        progress,
+        byte_counter,
+        interrupted: Arc::clone(interrupted),
+        stop_reason: stop_after(cli.timeout, interrupted),
+        streamed: None,
+        extension_stats: HashMap::new(),
+        timings: Vec::new(),
+        failed: Vec::new(),
+        in_use: Vec::new(),
+        unstable: Vec::new(),
+        copied: Vec::new(),
+        moved: Vec::new(),
+        print0,
+        manifest,
+        catalog: open_catalog(cli, &jobs[0].source, &jobs[0].destination),
+        catalog_failed: Cell::new(false),
+        csv_report: create_csv_report(cli),
+        html_report: cli.report_html.as_ref().map(|path| report::HtmlReport::new(path, &jobs[0].source, &jobs[0].destination)),
+        crypto: None,
+        undo,
+        watchdog,
+        hash_cache: open_hash_cache(cli),
+        sync_state,
+        attributed_dirs: HashSet::new(),
+        created_dirs: RefCell::new(HashSet::new()),
+    };
+    thread::sleep(Duration::from_millis(200));
+
+    let completed = run.copy_all(pending.into_iter().map(Ok), interrupt_rx)?;
+    let failed_fast = run.failed_fast();
+    run.conclude(completed, &walk_notes);
+    let destinations: Vec<&Path> = jobs
+        .iter()
+        .flat_map(|job| std::iter::once(&job.destination).chain(&job.cli.also_to))
+        .map(PathBuf::as_path)
+        .collect();
+    // An open lock file keeps the drive busy, so it could not be unmounted
+    drop(locks);
+    finish_media(cli, &destinations, completed);
+    if failed_fast {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Copy a block device to an image file, an image to a device, or one
+/// device to another, with the safety checks dd leaves out: the target must
+/// not be mounted, must be large enough, and is only overwritten after a
+/// confirmation
+fn copy_device(cli: &CopyArgs, yes_given: bool, source: &Path, destination: &Path, interrupted: &Arc<AtomicBool>) -> Result<()> {
+    let file_options = cli.link || cli.symlink.is_some() || !cli.encrypt.is_empty() || cli.decrypt.is_some() || cli.decompress
+        || cli.resume || cli.update || cli.link_dest.is_some() || !cli.also_to.is_empty() || cli.remove_sources
+        || cli.offset.is_some() || cli.length.is_some() || cli.backups.is_some();
+    if file_options {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("Block devices are copied as a whole; file options such as --link, --encrypt, --update or --also-to do not apply")
+        );
+        return Ok(());
+    }
 
-        // Check for interruption signal (non-blocking)
-        if interrupt_rx.try_recv().is_ok() {
-            progress.interrupted();
+    // A device copied into a folder becomes an image named after it
+    let destination = match destination.is_dir() {
+        true => destination.join(format!("{}.img", source.file_name().unwrap_or_default().to_string_lossy())),
+        false => destination.to_path_buf(),
+    };
+    let to_device = blockdev::is_block_device(&destination);
+    let size = match blockdev::size(source) {
+        Ok(size) => size,
+        Err(e) => {
+            eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("Could not read the size of {path}: {error}", path = style(source.display()).white(), error = e)
+            );
             return Ok(());
         }
+    };
+    if to_device {
+        if let Some(mount) = blockdev::mount_point(&destination) {
+            eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("{device} is mounted on {path}; unmount it before writing to it",
+                    device = style(destination.display()).white(),
+                    path = style(mount.display()).white()
+                )
+            );
+            return Ok(());
+        }
+        let capacity = blockdev::size(&destination).unwrap_or(0);
+        if capacity < size {
+            eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("{device} holds {capacity}, less than the {size} of {source}",
+                    device = style(destination.display()).white(),
+                    capacity = format_size(capacity as f64),
+                    size = format_size(size as f64),
+                    source = style(source.display()).white()
+                )
+            );
+            return Ok(());
+        }
+    }
+    if let Some(mount) = blockdev::mount_point(source).filter(|_| blockdev::is_block_device(source)) {
+        println!("{} {}", style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+            tr!("{device} is mounted on {path}; the image will be inconsistent if it changes during the copy",
+                device = style(source.display()).white(),
+                path = style(mount.display()).white()
+            )
+        );
+    }
+
+    println!("{}{} {}", icon!("💽 "), style(tr!("DEVICE COPY")).cyan().bold(),
+        tr!("{source} → {destination}", source = style(source.display()).white(), destination = style(destination.display()).white())
+    );
+    println!("{}{} {}", icon!("💾 "), tr!("Total size:"), style(format_size(size as f64)).green().bold());
+    if let Some(limit) = cli.bwlimit {
+        println!("{}{} {}", icon!("🐢 "), tr!("Limited to:"), style(format_rate(limit as f64)).yellow());
+    }
+    if to_device {
+        println!("{} {}", style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+            tr!("Everything on {device} will be overwritten", device = style(destination.display()).red().bold())
+        );
+    }
+
+    // Without prompts nothing can ask first, and a drive is not wiped unless --yes says so
+    if to_device && !yes_given && !cfg!(feature = "prompts") {
+        eprintln!("{} {}",
+            style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("Overwriting a device needs --yes in a build without confirmation prompts")
+        );
+        return Ok(());
+    }
 
-        // Check if source file still exists before copying
-        if !file.exists() {
-            println!("\n{} {} (file no longer exists)", 
-                style("❌ Skipped:").yellow().bold(),
-                style(file.display()).white()
+    #[cfg(feature = "prompts")]
+    if !cli.yes {
+        if !console::user_attended() {
+            eprintln!("{} {}",
+                style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("Confirmation required but not running interactively (pass --yes to skip it)")
             );
-            continue;
+            return Ok(());
+        }
+        // Overwriting a drive is the one copy that defaults to no
+        let confirmed = Confirm::new()
+            .with_prompt(if to_device { tr!("Overwrite the device?") } else { tr!("Start copying?") })
+            .default(!to_device)
+            .interact()?;
+        if !confirmed {
+            println!("{} {}", style(icon!("🚫 ", "CANCELLED:")).yellow().bold(), tr!("Nothing was copied"));
+            return Ok(());
+        }
+    }
+
+    let _sleep_inhibitor = (!cli.allow_sleep).then(inhibit::SleepInhibitor::acquire);
+    println!("{}{}", icon!("💡 "), tr!("Press Ctrl+C to safely stop the operation"));
+    println!();
+
+    let mut progress = AnimatedProgress::new(1, size);
+    let byte_counter = progress.byte_counter();
+    progress.terminal_title = cli.title;
+    progress.osc_progress = cli.osc_progress || cli.taskbar;
+    progress.altscreen = cli.altscreen;
+    progress.taskbar_progress = cli.taskbar;
+    progress.bell = cli.bell;
+    progress.templates = load_templates();
+    progress.set_current_file(source);
+    progress.start_animation(cli.low_animation);
+    status::listen(progress.status());
+    serve_progress(cli, &progress);
+    let stop_reason = stop_after(cli.timeout, interrupted);
+
+    match blockdev::copy(source, &destination, size, cli.bwlimit, &byte_counter, interrupted) {
+        Ok(_) => {
+            progress.increment();
+            progress.tally.copied = 1;
+            progress.finish();
         }
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+            progress.interrupted(stop_reason.get().copied().unwrap_or(StopReason::Interrupted));
+            return Ok(());
+        }
+        Err(e) => {
+            progress.record_failure();
+            progress.stop_animation();
+            eprintln!("\n{} {}", style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("Could not copy {source} to {destination}: {error}",
+                    source = style(source.display()).white(),
+                    destination = style(destination.display()).white(),
+                    error = e
+                )
+            );
+            std::process::exit(1);
+        }
+    }
+
+    if !cli.verify {
+        if to_device {
+            println!("{}{}", icon!("💡 "), tr!("Add --verify to read the device back and compare it with the source"));
+        }
+        finish_media(cli, &[&destination], true);
+        return Ok(());
+    }
 
-        let rel_path = file.strip_prefix(&source).unwrap_or(&file);
-        let dest_path = if source.is_file() {
-            if destination.is_dir() {
-                destination.join(
-                    source.file_name().unwrap_or_else(|| std::ffi::OsStr::new("unknown"))
+    // The write has been synced; read it back from the medium and compare block by block
+    let verifying = VerifyProgress::start(1, size);
+    let started = Instant::now();
+    let compared = blockdev::compare(source, &destination, size, &verifying.bytes, interrupted);
+    verifying.done.fetch_add(1, Ordering::Relaxed);
+    verifying.finish();
+    let completed = match compared {
+        Ok(None) => {
+            println!("{}{} {}", icon!("🔍 "),
+                style(tr!("VERIFIED!")).green().bold(),
+                tr!("{path} matches its source ({size} read back in {seconds}s)",
+                    path = style(destination.display()).white(),
+                    size = format_size(size as f64),
+                    seconds = format!("{:.2}", started.elapsed().as_secs_f32())
+                )
+            );
+            true
+        }
+        Ok(Some(difference)) => {
+            println!("{}{} {}", icon!("⚠️  "),
+                style(tr!("VERIFY FAILED!")).red().bold(),
+                tr!("{path} differs from its source in {blocks} blocks of 4 MiB, the first at byte {offset}",
+                    path = style(destination.display()).white(),
+                    blocks = style(difference.blocks).red().bold(),
+                    offset = style(difference.first).yellow()
                 )
+            );
+            if to_device {
+                println!("{}{}", icon!("💡 "), tr!("The medium may be failing or counterfeit (smaller than it claims); try another one"));
+            }
+            std::process::exit(1);
+        }
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+            println!("{}{} {}", icon!("🛑 "),
+                style(tr!("INTERRUPTED!")).red().bold(),
+                tr!("Verification stopped before every copy was checked")
+            );
+            false
+        }
+        Err(e) => {
+            eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), tr!("Could not verify {path}: {error}", path = destination.display(), error = e));
+            std::process::exit(1);
+        }
+    };
+    finish_media(cli, &[&destination], completed);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let Cli { command, copy } = Cli::parse();
+    // The copying commands share one engine; they differ only in these options
+    let (mut cli, command) = match command {
+        Some(Command::Cp(copy)) => (*copy, None),
+        Some(Command::Mv(mut copy)) => {
+            // Undoing a move would delete the only copy left
+            copy.remove_sources = true;
+            copy.no_undo = true;
+            (*copy, None)
+        }
+        Some(Command::Sync(mut copy)) => {
+            copy.update = true;
+            (*copy, None)
+        }
+        command => (copy, command),
+    };
+    // Builds without prompts have nothing to confirm with; overwriting a device still needs --yes
+    let yes_given = cli.yes;
+    if !cfg!(feature = "prompts") {
+        cli.yes = true;
+    }
+    cli.preserve = preserve::resolve(&cli.preserve, &cli.no_preserve);
+    SI_UNITS.store(cli.si, Ordering::Relaxed);
+    if cli.accessible {
+        ACCESSIBLE.store(true, Ordering::Relaxed);
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+
+    match &command {
+        Some(Command::Check(args)) => {
+            if !check::run(args)? {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Bench(args)) => return bench::run(args),
+        Some(Command::Undo(args)) => return undo::run(args),
+        Some(Command::History(args)) => return history::run(args),
+        Some(Command::Man(args)) => return man::run(args, Cli::command()),
+        Some(Command::Verify(args)) => {
+            if !verify::run(args)? {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Clean(args)) => return clean::run(args),
+        Some(Command::Restore(args)) => {
+            if !restore::run(args)? {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Cp(_) | Command::Mv(_) | Command::Sync(_)) | None => {}
+    }
+
+    if (cli.checksum || cli.sync_state.is_some()) && !cli.update {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), tr!("--checksum and --sync-state need --update (or copro sync)"));
+        return Ok(());
+    }
+
+    // What clap checks for --update, for the update copro sync turns on by itself
+    if cli.update && (cli.link || cli.symlink.is_some() || !cli.encrypt.is_empty() || cli.decrypt.is_some() || cli.decompress) {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("copro sync cannot be combined with --link, --symlink, --encrypt, --decrypt or --decompress")
+        );
+        return Ok(());
+    }
+
+    // A link to the source would dangle once it is removed, and a snapshot cannot be removed from
+    if cli.remove_sources && (cli.symlink.is_some() || cli.vss || cli.offset.is_some() || cli.length.is_some()) {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("copro mv cannot be combined with --symlink, --vss, --offset or --length")
+        );
+        return Ok(());
+    }
+
+    // Claim stdout before anything is printed, so the list stays clean
+    let print0 = cli.print0.then(print0::Print0::take_stdout).transpose()?;
+
+    // Set up signal handler for graceful shutdown
+    let (interrupt_rx, interrupted) = setup_signal_handler(cli.confirm_stop)?;
+
+    if let Some(path) = &cli.jobs_from {
+        return run_jobs(&cli, path, print0, &interrupt_rx, &interrupted);
+    }
+
+    let mut source_paths = cli.source.clone();
+    if source_paths.is_empty() {
+        source_paths.push(match cli.source_positional.clone() {
+            Some(path) => path,
+            None => ask_path(tr!("Select source path"), recent::Kind::Source)?,
+        });
+    }
+    let source = source_paths[0].clone();
+
+    // With sources given through -s, a single positional path is the destination
+    let positional_destination = match cli.source.is_empty() {
+        true => cli.destination_positional.clone(),
+        false => cli.destination_positional.clone().or(cli.source_positional.clone()),
+    };
+    let destination = match cli.destination.clone().or(positional_destination) {
+        Some(path) => path,
+        None => ask_path(tr!("Select destination path"), recent::Kind::Destination)?,
+    };
+    // Date placeholders name the folder of this run's snapshot
+    let now = datepath::Now::local();
+    let destination = now.expand(&destination);
+    cli.also_to = cli.also_to.iter().map(|extra| now.expand(extra)).collect();
+
+    // Check if source exists
+    if !source.exists() {
+        eprintln!("{} {}",
+            style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("Source path does not exist: {path}", path = style(source.display()).white())
+        );
+        return Ok(());
+    }
+
+    // Check if source and destination are the same path
+    let resolved_source = resolve_path(&source);
+    let resolved_destination = resolve_path(&destination);
+    if resolved_source == resolved_destination || same_file(&source, &destination) {
+        eprintln!("{} {}",
+            style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("Source and destination are the same: {path}", path = style(resolved_source.display()).white())
+        );
+        return Ok(());
+    }
+
+    // Drives and partitions are copied byte for byte, like dd
+    if blockdev::is_block_device(&source) || blockdev::is_block_device(&destination) {
+        return copy_device(&cli, yes_given, &source, &destination, &interrupted);
+    }
+
+    // Check if destination is inside the source tree
+    let nested_destination = source.is_dir() && resolved_destination.starts_with(&resolved_source);
+    if nested_destination && !cli.force {
+        eprintln!("{} {}",
+            style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("Destination is inside the source tree: {path} (use --force to copy anyway)",
+                path = style(destination.display()).white()
+            )
+        );
+        return Ok(());
+    }
+
+    // Hardlinks cannot span filesystems
+    if cli.link && !same_filesystem(&resolved_source, &resolved_destination) {
+        eprintln!("{} {}",
+            style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("--link requires source and destination on the same filesystem")
+        );
+        return Ok(());
+    }
+
+    if (cli.chmod.is_some() || cli.chown.is_some()) && !cfg!(unix) {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), tr!("--chmod and --chown are only available on Unix"));
+        return Ok(());
+    }
+
+    if (cli.usermap.is_some() || cli.groupmap.is_some() || cli.fake_super) && !cli.preserve.contains(&Preserve::Owner) {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), tr!("--usermap, --groupmap and --fake-super need --preserve=owner"));
+        return Ok(());
+    }
+
+    if let Some(previous) = cli.link_dest.as_ref().filter(|previous| !previous.is_dir()) {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(),
+            tr!("--link-dest {path} is not a directory", path = previous.display())
+        );
+        return Ok(());
+    }
+
+    if cli.ads && !cfg!(windows) {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), tr!("--ads is only available on Windows"));
+        return Ok(());
+    }
+
+    if let Some(reason) = cli.preserve.iter().find_map(|p| p.unsupported()) {
+        eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), i18n::text(reason));
+        return Ok(());
+    }
+
+    // Merging only makes sense for directories; each one gets the main safety checks
+    if source_paths.len() > 1 {
+        for path in &source_paths {
+            let resolved = resolve_path(path);
+            let problem = if !path.is_dir() {
+                Some(tr!("is not a directory (only directories can be merged)"))
+            } else if resolved == resolved_destination {
+                Some(tr!("is the same as the destination"))
+            } else if path != &source && resolved_destination.starts_with(&resolved) {
+                Some(tr!("contains the destination"))
             } else {
-                destination.clone()
+                None
+            };
+            if let Some(problem) = problem {
+                eprintln!("{} {} {} {}",
+                    style(icon!("❌ ", "ERROR:")).red().bold(),
+                    tr!("Source"),
+                    style(path.display()).white(),
+                    problem
+                );
+                return Ok(());
             }
-        } else {
-            destination.join(rel_path)
-        };
-    
-        if let Some(parent) = dest_path.parent() {
-            fs::create_dir_all(parent)?;
         }
-    
-        // Use safe copy with temporary file (unless fast mode)
-        let copy_result = if cli.fast_mode {
-            fs::copy(&file, &dest_path)
+    }
+
+    // Each extra destination gets the same safety checks as the main one
+    for extra in &cli.also_to {
+        let resolved_extra = resolve_path(extra);
+        let inside_source = source_paths.iter().any(|path| path.is_dir() && resolved_extra.starts_with(resolve_path(path)));
+        let problem = if resolved_extra == resolved_source || same_file(&source, extra) {
+            Some(tr!("is the same as the source"))
+        } else if resolved_extra == resolved_destination {
+            Some(tr!("is the same as the destination"))
+        } else if inside_source {
+            Some(tr!("is inside the source tree"))
         } else {
-            copy_file_with_temp(&file, &dest_path)
+            None
         };
+        if let Some(problem) = problem {
+            eprintln!("{} --also-to {} {}",
+                style(icon!("❌ ", "ERROR:")).red().bold(),
+                style(extra.display()).white(),
+                problem
+            );
+            return Ok(());
+        }
+    }
 
-        match copy_result {
-            Ok(bytes_copied) => {
-                if bytes_copied == 0 {
-                    println!("\n{} {} (0 bytes copied - file may be empty)", 
-                        style("⚠️ Warning:").yellow().bold(),
-                        style(file.display()).white()
-                    );
-                }
-                progress.increment();
-                if cli.verbose {
-                    println!("\n{} {} ({} bytes)", 
-                        style("✅ Success:").green().bold(),
-                        style(file.display()).white(),
-                        style(bytes_copied).cyan()
-                    );
+    for path in &source_paths {
+        recent::remember(recent::Kind::Source, path);
+    }
+    recent::remember(recent::Kind::Destination, &destination);
+
+    // Load keys up front so a typo fails before anything is written
+    let crypto = if !cli.encrypt.is_empty() {
+        Some(Crypto::encrypt(&cli.encrypt))
+    } else {
+        cli.decrypt.as_deref().map(Crypto::decrypt)
+    };
+    let crypto = match crypto.transpose() {
+        Ok(crypto) => crypto,
+        Err(e) => {
+            eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), e);
+            return Ok(());
+        }
+    };
+
+    // Read from a point-in-time snapshot so databases and mail stores held open stay consistent
+    let snapshots = if cli.vss {
+        match vss::Snapshots::create(&source_paths) {
+            Ok(snapshots) => {
+                for (volume, device) in snapshots.volumes() {
+                    println!("{}{}", icon!("📸 "), tr!("Shadow copy of {volume}: {device}", volume = style(volume.display()).white(), device = device.display()));
                 }
+                Some(snapshots)
             }
             Err(e) => {
-                println!("\n{} {} ({})", 
-                    style("❌ Failed:").red().bold(),
-                    style(file.display()).white(),
-                    style(e).red()
+                eprintln!("{} {}", style(icon!("❌ ", "ERROR:")).red().bold(), e);
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+    let read_paths: Vec<PathBuf> = match &snapshots {
+        Some(snapshots) => source_paths.iter().map(|path| snapshots.map(path)).collect(),
+        None => source_paths,
+    };
+    let skip_dir = nested_destination.then(|| match &snapshots {
+        Some(snapshots) => snapshots.map(&resolved_destination),
+        None => resolved_destination.clone(),
+    });
+    let skip_dir = skip_dir.as_deref();
+
+    // With --yes there is nothing to confirm, so a directory is scanned while it is copied
+    let scan_while_copying = cli.yes && !cli.list_only && !cli.select && read_paths.len() == 1 && read_paths[0].is_dir();
+    let mut spinner = (!scan_while_copying).then(ScanSpinner::start);
+    let walk_notes = walk::Notes::default();
+
+    // Files a --merge policy or --select leaves out, for the results
+    let mut left_out = 0;
+
+    // Merging needs every listing up front; a single source is walked lazily
+    let (sources, merged) = if read_paths.len() > 1 {
+        let mut listings = Vec::new();
+        for path in &read_paths {
+            let listing = walk::Walker::new(path, skip_dir, cli.broken_symlinks, &walk_notes)
+                .inspect(|file| {
+                    if let (Some(spinner), Ok(file)) = (&spinner, file) {
+                        spinner.found(file.size());
+                    }
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            listings.push(listing);
+        }
+        if let Some(spinner) = spinner.take() {
+            spinner.finish();
+        }
+        match merge::merge(read_paths, listings, cli.merge) {
+            Ok((sources, files, decisions)) => {
+                print_merge_decisions(&decisions);
+                left_out += decisions.iter().flat_map(|d| &d.others).filter(|(_, renamed)| renamed.is_none()).count();
+                (sources, Some(files))
+            }
+            Err(conflicts) => {
+                eprintln!("{} {}",
+                    style(icon!("❌ ", "ERROR:")).red().bold(),
+                    tr!("{count} paths exist in more than one source (choose a --merge policy):", count = conflicts.len())
                 );
-                
-                // Clean up any partial temporary files
-                if !cli.fast_mode {
-                    let temp_dest = dest_path.with_extension(
-                        format!("{}.tmp", 
-                            dest_path.extension()
-                                .and_then(|s| s.to_str())
-                                .unwrap_or("tmp")
-                        )
-                    );
-                    let _ = fs::remove_file(&temp_dest);
+                for relative in conflicts {
+                    eprintln!("   {}", relative.display());
                 }
+                return Ok(());
             }
         }
-        
-        // Add slight delay between files to show animation better
-        thread::sleep(Duration::from_millis(50));
+    } else {
+        (Sources::single(read_paths[0].clone()), None)
+    };
+    let scanned = || -> Box<dyn Iterator<Item = io::Result<walk::FileEntry>> + '_> {
+        match &merged {
+            Some(files) => Box::new(files.iter().cloned().map(Ok)),
+            None => Box::new(walk::Walker::new(&sources.roots[0], skip_dir, cli.broken_symlinks, &walk_notes)),
+        }
+    };
+
+    // --select needs the whole tree up front to show it
+    let selection = if cli.select {
+        let listing = scanned()
+            .inspect(|file| {
+                if let (Some(spinner), Ok(file)) = (&spinner, file) {
+                    spinner.found(file.size());
+                }
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        if let Some(spinner) = spinner.take() {
+            spinner.finish();
+        }
+        let listed = listing.len();
+        match choose_files(listing, &sources)? {
+            Some(chosen) => {
+                left_out += listed - chosen.len();
+                Some(chosen)
+            }
+            None => {
+                println!("{} {}", style(icon!("🚫 ", "CANCELLED:")).yellow().bold(), tr!("Nothing was copied"));
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+    // Listed once here and copied from this list, so no file is stat'ed twice
+    let (listing, mut total_bytes) = if scan_while_copying {
+        (Vec::new(), 0)
+    } else {
+        let files = match selection {
+            Some(chosen) => Box::new(chosen.into_iter().map(Ok)),
+            None => scanned(),
+        };
+        let tally = walk::tally(files, |bytes| {
+            if let Some(spinner) = &spinner {
+                spinner.found(bytes);
+            }
+        });
+        if let Some(spinner) = spinner {
+            spinner.finish();
+        }
+        tally?
+    };
+    let file_count = listing.len();
+
+    // Resolve the byte range for partial single-file copies
+    let range = if cli.offset.is_some() || cli.length.is_some() {
+        if !source.is_file() {
+            eprintln!("{} {}",
+                style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("--offset/--length require a single source file")
+            );
+            return Ok(());
+        }
+        let offset = cli.offset.unwrap_or(0);
+        if offset > total_bytes {
+            eprintln!("{} {}",
+                style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("Offset {offset} is beyond the end of the file ({size} bytes)", offset = offset, size = total_bytes)
+            );
+            return Ok(());
+        }
+        let available = total_bytes - offset;
+        let length = cli.length.map_or(available, |len| len.min(available));
+        total_bytes = length;
+        Some((offset, length))
+    } else {
+        None
+    };
+
+    // A single file given --resume is streamed, so an interrupted copy leaves
+    // partial data (and checkpoints) that a later run can continue from
+    let streamed = range.or_else(|| {
+        (cli.resume && source.is_file() && !cli.link && cli.symlink.is_none() && crypto.is_none()).then_some((0, total_bytes))
+    });
+
+    
+    // Check if there are any files to copy
+    if file_count == 0 && !scan_while_copying {
+        println!("{} {}",
+            style(icon!("⚠️ ", "WARNING:")).yellow().bold(),
+            tr!("No files found to copy from: {path}", path = style(source.display()).white())
+        );
+        print_walk_notes(&walk_notes);
+        return Ok(());
     }
 
-    progress.finish();
+    // Show what would be copied where, without touching the destination
+    if cli.list_only {
+        for walk::FileEntry { path: file, .. } in &listing {
+            let dest_path = output_path(file, &sources, &destination, crypto.as_ref(), cli.decompress);
+            println!("{} → {}", file.display(), dest_path.display());
+            for extra in &cli.also_to {
+                let extra_path = output_path(file, &sources, extra, crypto.as_ref(), cli.decompress);
+                println!("{} → {}", file.display(), extra_path.display());
+            }
+        }
+        println!("{}{}", icon!("📋 "),
+            tr!("{count} files, {size} would be copied",
+                count = style(file_count).yellow().bold(),
+                size = style(format_size(total_bytes as f64)).green().bold()
+            )
+        );
+        print_walk_notes(&walk_notes);
+        return Ok(());
+    }
+    
+    println!("{}{} {}", icon!("🚀 "), style(tr!("INITIALIZING")).cyan().bold(), tr!("Starting copy operation..."));
+    if scan_while_copying {
+        println!("{}{} {}", icon!("📁 "), tr!("Files to copy:"), style(tr!("counting while copying")).yellow());
+    } else {
+        println!("{}{} {}", icon!("📁 "), tr!("Files to copy:"), style(file_count).yellow().bold());
+        println!("{}{} {}", icon!("💾 "), tr!("Total size:"), style(format_size(total_bytes as f64)).green().bold());
+    }
+    if let Some((offset, length)) = range {
+        println!("{}{} {}..{}", icon!("✂️  "), tr!("Byte range:"), style(offset).cyan(), style(offset + length).cyan());
+    }
+    println!("{}{} {}", icon!("🎯 "), tr!("Destination:"), style(destination.display()).white());
+    for extra in &cli.also_to {
+        println!("{}{} {}", icon!("🎯 "), tr!("Also to:"), style(extra.display()).white());
+    }
+    println!("{}{} {}", icon!("♻️  "), tr!("Existing files:"), style(existing_files_policy(&cli)).yellow());
+
+    #[cfg(feature = "prompts")]
+    if !cli.yes {
+        if !console::user_attended() {
+            eprintln!("{} {}",
+                style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("Confirmation required but not running interactively (pass --yes to skip it)")
+            );
+            return Ok(());
+        }
+        let confirmed = Confirm::new()
+            .with_prompt(tr!("Start copying?"))
+            .default(true)
+            .interact()?;
+        if !confirmed {
+            println!("{} {}", style(icon!("🚫 ", "CANCELLED:")).yellow().bold(), tr!("Nothing was copied"));
+            return Ok(());
+        }
+    }
+
+    // A second run into the same destination stops here rather than interleaving with this one
+    let targets: Vec<(&Path, &Path)> = std::iter::once(&destination).chain(&cli.also_to).map(|destination| (source.as_path(), destination.as_path())).collect();
+    let Some(locks) = lock_destinations(&targets) else {
+        return Ok(());
+    };
+    // Released when main returns, whether the run finished or stopped
+    let _sleep_inhibitor = (!cli.allow_sleep).then(inhibit::SleepInhibitor::acquire);
+    let Ok(sync_state) = load_sync_state(&cli) else {
+        return Ok(());
+    };
+    let undo = start_undo_log(&cli);
+
+    println!("{}{}", icon!("💡 "), tr!("Press Ctrl+C to safely stop the operation"));
+    println!();
+
+    let mut progress = AnimatedProgress::new(file_count, total_bytes);
+    let byte_counter = progress.byte_counter();
+    progress.terminal_title = cli.title;
+    progress.osc_progress = cli.osc_progress || cli.taskbar;
+    progress.altscreen = cli.altscreen;
+    progress.taskbar_progress = cli.taskbar;
+    progress.bell = cli.bell;
+    progress.templates = load_templates();
+    progress.tally.skipped = left_out;
+    let watchdog = start_watchdog(&cli, &mut progress, &interrupted);
+    let queue = scan_while_copying.then(|| walk::spawn(&sources.roots[0], skip_dir, cli.broken_symlinks, &walk_notes, progress.scan_progress()));
+    progress.start_animation(cli.low_animation);
+    status::listen(progress.status());
+    serve_progress(&cli, &progress);
+
+    let stop_reason = stop_after(cli.timeout, &interrupted);
+
+    let mut run = Run {
+        cli: &cli,
+        sources: &sources,
+        progress,
+        byte_counter,
+        interrupted: Arc::clone(&interrupted),
+        stop_reason,
+        streamed,
+        extension_stats: HashMap::new(),
+        timings: Vec::new(),
+        failed: Vec::new(),
+        in_use: Vec::new(),
+        unstable: Vec::new(),
+        copied: Vec::new(),
+        moved: Vec::new(),
+        print0,
+        manifest: cli.manifest.is_some().then(|| Manifest::new(&source, &destination)),
+        catalog: open_catalog(&cli, &source, &destination),
+        catalog_failed: Cell::new(false),
+        csv_report: create_csv_report(&cli),
+        html_report: cli.report_html.as_ref().map(|path| report::HtmlReport::new(path, &source, &destination)),
+        crypto: crypto.as_ref(),
+        undo,
+        watchdog,
+        hash_cache: open_hash_cache(&cli),
+        sync_state,
+        attributed_dirs: HashSet::new(),
+        created_dirs: RefCell::new(HashSet::new()),
+    };
+
+    // Small delay to let animation start
+    thread::sleep(Duration::from_millis(200));
+
+    let pending: Box<dyn Iterator<Item = io::Result<walk::FileEntry>>> = match queue {
+        Some(queue) => Box::new(queue.into_iter()),
+        None => Box::new(listing.into_iter().map(Ok)),
+    };
+    let pending = pending.map(|file| {
+        let walk::FileEntry { path: file, metadata } = file?;
+        let dest_path = output_path(&file, &sources, &destination, crypto.as_ref(), cli.decompress);
+        Ok(Pending { file, metadata, destination: dest_path, root: &destination, cli: &cli })
+    });
+    let completed = run.copy_all(pending, &interrupt_rx)?;
+    let failed_fast = run.failed_fast();
+    run.conclude(completed, &walk_notes);
+    let destinations: Vec<&Path> = std::iter::once(destination.as_path()).chain(cli.also_to.iter().map(PathBuf::as_path)).collect();
+    // An open lock file keeps the drive busy, so it could not be unmounted
+    drop(locks);
+    finish_media(&cli, &destinations, completed);
+    if failed_fast {
+        // process::exit drops nothing: delete the --vss shadow copies first
+        drop(snapshots);
+        std::process::exit(1);
+    }
     Ok(())
 }
\ No newline at end of file
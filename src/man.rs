@@ -0,0 +1,24 @@
+use anyhow::{Context, Result};
+use console::style;
+use std::{io, path::PathBuf};
+
+/// Arguments of `copro man`
+#[derive(clap::Args, Clone)]
+pub struct ManArgs {
+    /// write copro.1 and a page for each subcommand (copro-check.1, ...) into this directory
+    /// instead of printing copro.1
+    #[arg(long, value_name = "DIR")]
+    dir: Option<PathBuf>,
+}
+
+/// Print the man page generated from the command line definitions, or write
+/// the pages of copro and its subcommands for a package to install
+pub fn run(args: &ManArgs, command: clap::Command) -> Result<()> {
+    let Some(dir) = &args.dir else {
+        return clap_mangen::Man::new(command).render(&mut io::stdout().lock()).context("could not write the man page");
+    };
+    std::fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+    clap_mangen::generate_to(command, dir).with_context(|| format!("could not write the man pages to {}", dir.display()))?;
+    println!("{}{}", icon!("📖 "), tr!("Man pages written to {path}", path = style(dir.display()).white()));
+    Ok(())
+}
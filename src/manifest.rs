@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Audit record of a copy run, written with `--manifest`
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    /// Unix time (seconds) the run started
+    pub created: u64,
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub size: u64,
+    /// Source modification time as Unix seconds
+    pub mtime: Option<u64>,
+    /// BLAKE3 hash of the data written, hex encoded
+    pub hash: Option<String>,
+    pub result: EntryResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryResult {
+    Copied,
+    Failed,
+}
+
+impl Manifest {
+    pub fn new(source: &Path, destination: &Path) -> Self {
+        Self {
+            version: 1,
+            created: unix_seconds(SystemTime::now()).unwrap_or(0),
+            source: absolute(source),
+            destination: absolute(destination),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read(path)?;
+        serde_json::from_slice(&content).map_err(io::Error::other)
+    }
+
+    /// Turn the entry written to `destination` into a failure, e.g. after a failed verification
+    pub fn mark_failed(&mut self, destination: &Path, error: String) {
+        let destination = absolute(destination);
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.destination == destination) {
+            entry.result = EntryResult::Failed;
+            entry.error = Some(error);
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+impl ManifestEntry {
+    /// Entry for a file that was written to `destination`; without the `hash`
+    /// of the data taken during the copy, hashes what landed there
+    pub fn copied(source: &Path, destination: &Path, size: u64, hash: Option<blake3::Hash>) -> Self {
+        let hash = match hash {
+            Some(hash) => Some(hash.to_hex().to_string()),
+            None => hash_file(destination).ok(),
+        };
+        Self {
+            source: absolute(source),
+            destination: absolute(destination),
+            size,
+            mtime: source_mtime(source),
+            hash,
+            result: EntryResult::Copied,
+            error: None,
+        }
+    }
+
+    pub fn failed(source: &Path, destination: &Path, error: String) -> Self {
+        Self {
+            source: absolute(source),
+            destination: absolute(destination),
+            size: fs::metadata(source).map(|m| m.len()).unwrap_or(0),
+            mtime: source_mtime(source),
+            hash: None,
+            result: EntryResult::Failed,
+            error: Some(error),
+        }
+    }
+}
+
+/// Hex BLAKE3 hash of a whole file
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(fs::File::open(path)?)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+fn source_mtime(path: &Path) -> Option<u64> {
+    fs::metadata(path).and_then(|m| m.modified()).ok().and_then(unix_seconds)
+}
+
+fn unix_seconds(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,163 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A mounted filesystem, or a bare device, that --eject can take out
+pub struct Volume {
+    /// The device the filesystem lives on (`/dev/sdb1`)
+    pub device: PathBuf,
+    /// Where it is mounted; none for a device written to directly
+    pub mount: Option<PathBuf>,
+}
+
+/// Flush everything written to the filesystem holding `path` to its device:
+/// syncfs() on Linux, a sync() of every filesystem elsewhere on Unix
+pub fn sync_filesystem(path: &Path) -> io::Result<()> {
+    // A destination that was never created lives on its parent's filesystem
+    let path = path.ancestors().find(|dir| dir.exists()).unwrap_or(path);
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        let file = fs::File::open(path)?;
+        // SAFETY: syncfs only takes the descriptor, which `file` keeps open
+        if unsafe { libc::syncfs(file.as_raw_fd()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        fs::metadata(path)?;
+        // SAFETY: sync takes no arguments and cannot fail
+        unsafe { libc::sync() };
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        fs::metadata(path)?;
+        Err(unsupported())
+    }
+}
+
+/// The volume `path` is on: the device itself for a block device, otherwise
+/// the filesystem mounted deepest above it
+#[cfg(target_os = "linux")]
+pub fn volume(path: &Path) -> io::Result<Volume> {
+    if crate::blockdev::is_block_device(path) {
+        return Ok(Volume { device: path.to_path_buf(), mount: None });
+    }
+    let path = fs::canonicalize(path)?;
+    let mounts = fs::read_to_string("/proc/self/mounts")?;
+    // Later lines are mounted over earlier ones, so the last deepest match wins
+    let (device, mount) = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (device, mount) = (fields.next()?, PathBuf::from(crate::blockdev::unescape(fields.next()?)));
+            path.starts_with(&mount).then(|| (device.to_string(), mount))
+        })
+        .fold(None, |deepest: Option<(String, PathBuf)>, (device, mount)| match deepest {
+            Some(deepest) if deepest.1.components().count() > mount.components().count() => Some(deepest),
+            _ => Some((device, mount)),
+        })
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no filesystem is mounted there"))?;
+    if mount == Path::new("/") || !crate::blockdev::is_block_device(Path::new(&device)) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} is not on a removable drive (it is on {device}, mounted on {})", path.display(), mount.display()),
+        ));
+    }
+    Ok(Volume { device: PathBuf::from(device), mount: Some(mount) })
+}
+
+/// The volume `path` is on, as statfs() names it
+#[cfg(target_os = "macos")]
+pub fn volume(path: &Path) -> io::Result<Volume> {
+    use std::{ffi::CStr, os::unix::ffi::OsStrExt};
+
+    if crate::blockdev::is_block_device(path) {
+        return Ok(Volume { device: path.to_path_buf(), mount: None });
+    }
+    let name = std::ffi::CString::new(fs::canonicalize(path)?.as_os_str().as_bytes())?;
+    // SAFETY: statfs fills the zeroed struct it is given and reads a NUL-terminated path
+    let mut info: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(name.as_ptr(), &mut info) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let text = |field: &[libc::c_char]| {
+        // SAFETY: the kernel NUL-terminates the names within their fixed-size fields
+        let name = unsafe { CStr::from_ptr(field.as_ptr()) };
+        PathBuf::from(std::ffi::OsStr::from_bytes(name.to_bytes()))
+    };
+    let mount = text(&info.f_mntonname);
+    if mount == Path::new("/") {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} is on the system volume", path.display())));
+    }
+    Ok(Volume { device: text(&info.f_mntfromname), mount: Some(mount) })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn volume(_path: &Path) -> io::Result<Volume> {
+    Err(unsupported())
+}
+
+/// Unmount `volume` and power its drive off, so it can be pulled out. On
+/// Linux this goes through udisks, which lets a desktop user do it without
+/// root, and falls back to umount and eject (the drive is safe to pull once
+/// unmounted, so a missing eject is not an error); on macOS through diskutil.
+#[cfg(target_os = "linux")]
+pub fn eject(volume: &Volume) -> io::Result<()> {
+    let device = volume.device.as_os_str();
+    let udisks = match &volume.mount {
+        Some(_) => command("udisksctl", &["unmount".as_ref(), "--no-user-interaction".as_ref(), "-b".as_ref(), device]),
+        None => Ok(()),
+    };
+    match udisks {
+        Ok(()) => command("udisksctl", &["power-off".as_ref(), "--no-user-interaction".as_ref(), "-b".as_ref(), device]),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if let Some(mount) = &volume.mount {
+                command("umount", &[mount.as_os_str()])?;
+            }
+            match command("eject", &[device]) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                ejected => ejected,
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn eject(volume: &Volume) -> io::Result<()> {
+    let target = volume.mount.as_deref().unwrap_or(&volume.device);
+    command("diskutil", &["eject".as_ref(), target.as_os_str()])
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn eject(_volume: &Volume) -> io::Result<()> {
+    Err(unsupported())
+}
+
+/// Run `program`, turning a failure into an error that carries what it printed
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn command(program: &str, args: &[&std::ffi::OsStr]) -> io::Result<()> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("{program}: {e}")))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(io::Error::other(match message.is_empty() {
+        true => format!("{program} failed ({})", output.status),
+        false => message,
+    }))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "not supported on this platform")
+}
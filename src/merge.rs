@@ -0,0 +1,148 @@
+use crate::walk::FileEntry;
+use clap::ValueEnum;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// How to settle a relative path that exists in more than one source
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MergePolicy {
+    /// keep the most recently modified file
+    Newest,
+    /// keep the largest file
+    Largest,
+    /// refuse to copy anything while sources overlap
+    Error,
+    /// copy every version, numbering the later ones (`name (2).ext`)
+    KeepBoth,
+}
+
+/// The source roots of a run and where their files land relative to the destination
+pub struct Sources {
+    pub roots: Vec<PathBuf>,
+    renamed: HashMap<PathBuf, PathBuf>,
+}
+
+/// One conflict and how the policy settled it
+pub struct Decision {
+    pub relative: PathBuf,
+    pub kept: PathBuf,
+    /// Other versions: skipped, or renamed to the given relative path with keep-both
+    pub others: Vec<(PathBuf, Option<PathBuf>)>,
+}
+
+impl Sources {
+    pub fn single(root: PathBuf) -> Self {
+        Self { roots: vec![root], renamed: HashMap::new() }
+    }
+
+    /// The root `file` was found under (the deepest one, should roots be nested)
+    pub fn root_of(&self, file: &Path) -> &Path {
+        self.roots
+            .iter()
+            .filter(|root| file.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .unwrap_or(&self.roots[0])
+    }
+
+    /// Destination-relative path chosen for `file` by keep-both, if it was renamed
+    pub fn renamed(&self, file: &Path) -> Option<&Path> {
+        self.renamed.get(file).map(PathBuf::as_path)
+    }
+}
+
+/// Combine the files of several directory sources (`files[i]` lists `roots[i]`),
+/// applying `policy` wherever two sources share a relative path. Returns the
+/// files to copy in source order, or every conflict when the policy is `Error`.
+pub fn merge(
+    roots: Vec<PathBuf>,
+    files: Vec<Vec<FileEntry>>,
+    policy: MergePolicy,
+) -> Result<(Sources, Vec<FileEntry>, Vec<Decision>), Vec<PathBuf>> {
+    let mut order: Vec<PathBuf> = Vec::new();
+    let mut by_relative: HashMap<PathBuf, Vec<FileEntry>> = HashMap::new();
+    for (root, files) in roots.iter().zip(files) {
+        for file in files {
+            let relative = file.path.strip_prefix(root).unwrap_or(&file.path).to_path_buf();
+            let versions = by_relative.entry(relative.clone()).or_default();
+            if versions.is_empty() {
+                order.push(relative);
+            }
+            versions.push(file);
+        }
+    }
+
+    let conflicts: Vec<PathBuf> = order.iter().filter(|r| by_relative[*r].len() > 1).cloned().collect();
+    if policy == MergePolicy::Error && !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+
+    let mut taken: HashSet<PathBuf> = order.iter().cloned().collect();
+    let mut renamed = HashMap::new();
+    let mut selected = Vec::new();
+    let mut decisions = Vec::new();
+    for relative in order {
+        let mut versions = by_relative.remove(&relative).unwrap_or_default();
+        if versions.len() == 1 {
+            selected.append(&mut versions);
+            continue;
+        }
+
+        let winner = match policy {
+            MergePolicy::Newest => pick_max(&versions, |m| m.modified().unwrap_or(SystemTime::UNIX_EPOCH)),
+            MergePolicy::Largest => pick_max(&versions, |m| m.len()),
+            MergePolicy::KeepBoth | MergePolicy::Error => 0,
+        };
+        let kept = versions.remove(winner);
+        let kept_path = kept.path.clone();
+        selected.push(kept);
+
+        let others = versions
+            .into_iter()
+            .enumerate()
+            .map(|(index, file)| {
+                let path = file.path.clone();
+                if policy != MergePolicy::KeepBoth {
+                    return (path, None);
+                }
+                let target = numbered(&relative, index + 2, &taken);
+                taken.insert(target.clone());
+                renamed.insert(path.clone(), target.clone());
+                selected.push(file);
+                (path, Some(target))
+            })
+            .collect();
+        decisions.push(Decision { relative, kept: kept_path, others });
+    }
+
+    Ok((Sources { roots, renamed }, selected, decisions))
+}
+
+/// Index of the version with the greatest key, from the metadata the walk
+/// read; the earliest source wins ties
+fn pick_max<K: Ord>(versions: &[FileEntry], key: impl Fn(&fs::Metadata) -> K) -> usize {
+    let keys: Vec<Option<K>> = versions.iter().map(|v| v.metadata.as_ref().map(&key)).collect();
+    let mut best = 0;
+    for (index, candidate) in keys.iter().enumerate().skip(1) {
+        if candidate > &keys[best] {
+            best = index;
+        }
+    }
+    best
+}
+
+/// `dir/name (n).ext`, bumping `n` until the path is unused
+fn numbered(relative: &Path, mut n: usize, taken: &HashSet<PathBuf>) -> PathBuf {
+    let stem = relative.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = relative.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    loop {
+        let candidate = relative.with_file_name(format!("{} ({}){}", stem, n, extension));
+        if !taken.contains(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
@@ -0,0 +1,150 @@
+use std::{fs, io, path::Path};
+
+/// `--chmod`: octal modes for the files written and, with a `D` prefix, for
+/// the directories created for them (`644`, `D755,F644`)
+#[derive(Clone, Copy, Default)]
+pub struct Modes {
+    pub file: Option<u32>,
+    pub dir: Option<u32>,
+}
+
+/// `--chown`: either part may be left out, and each may be a name or a numeric ID
+#[derive(Clone, Copy)]
+pub struct Owner {
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+}
+
+/// `--usermap` / `--groupmap`: IDs to give the copies in place of the
+/// source's, when --preserve=owner carries ownership between systems whose
+/// numbering differs. The first matching rule wins; `*` matches any ID.
+#[derive(Clone)]
+pub struct IdMap {
+    rules: Vec<(Option<u32>, u32)>,
+}
+
+impl IdMap {
+    /// The ID `id` becomes on the destination
+    pub fn map(&self, id: u32) -> u32 {
+        self.rules
+            .iter()
+            .find(|(from, _)| from.is_none_or(|from| from == id))
+            .map_or(id, |(_, to)| *to)
+    }
+}
+
+pub fn parse_user_map(value: &str) -> Result<IdMap, String> {
+    parse_map(value, user_id, "user")
+}
+
+pub fn parse_group_map(value: &str) -> Result<IdMap, String> {
+    parse_map(value, group_id, "group")
+}
+
+/// `old:new` pairs separated by commas, or the path of a file with one pair
+/// per line (blank lines and `#` comments ignored)
+fn parse_map(value: &str, lookup: fn(&str) -> Option<u32>, kind: &str) -> Result<IdMap, String> {
+    let content;
+    let pairs: Vec<&str> = if value.contains(':') {
+        value.split(',').collect()
+    } else {
+        content = fs::read_to_string(value).map_err(|e| format!("could not read {value}: {e}"))?;
+        content.lines().map(|line| line.split('#').next().unwrap_or_default()).collect()
+    };
+    let id = |name: &str| name.parse().ok().or_else(|| lookup(name)).ok_or_else(|| format!("unknown {kind}: {name}"));
+
+    let mut rules = Vec::new();
+    for pair in pairs.iter().map(|pair| pair.trim()).filter(|pair| !pair.is_empty()) {
+        let (from, to) = pair.split_once(':').ok_or_else(|| format!("invalid mapping: {pair} (expected old:new)"))?;
+        let from = match from.trim() {
+            "*" => None,
+            from => Some(id(from)?),
+        };
+        rules.push((from, id(to.trim())?));
+    }
+    Ok(IdMap { rules })
+}
+
+pub fn parse_modes(value: &str) -> Result<Modes, String> {
+    let mut modes = Modes::default();
+    for item in value.split(',').map(str::trim) {
+        let (slot, octal) = match item.split_at_checked(1) {
+            Some(("D" | "d", octal)) => (&mut modes.dir, octal),
+            Some(("F" | "f", octal)) => (&mut modes.file, octal),
+            _ => (&mut modes.file, item),
+        };
+        let mode = u32::from_str_radix(octal, 8)
+            .ok()
+            .filter(|mode| !octal.is_empty() && *mode <= 0o7777)
+            .ok_or_else(|| format!("invalid mode: {item} (expected octal, e.g. 644 or D755,F644)"))?;
+        *slot = Some(mode);
+    }
+    Ok(modes)
+}
+
+pub fn parse_owner(value: &str) -> Result<Owner, String> {
+    let (user, group) = value.split_once(':').unwrap_or((value, ""));
+    let uid = match user {
+        "" => None,
+        user => Some(user.parse().ok().or_else(|| user_id(user)).ok_or_else(|| format!("unknown user: {user}"))?),
+    };
+    let gid = match group {
+        "" => None,
+        group => Some(group.parse().ok().or_else(|| group_id(group)).ok_or_else(|| format!("unknown group: {group}"))?),
+    };
+    if uid.is_none() && gid.is_none() {
+        return Err(format!("invalid owner: {value} (expected user, user:group or :group)"));
+    }
+    Ok(Owner { uid, gid })
+}
+
+/// Give `path`, just written, the requested owner and then mode (changing
+/// the owner can clear setuid bits)
+#[cfg(unix)]
+pub fn apply(path: &Path, mode: Option<u32>, owner: Option<Owner>) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Some(owner) = owner {
+        std::os::unix::fs::chown(path, owner.uid, owner.gid)?;
+    }
+    if let Some(mode) = mode {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn apply(_path: &Path, mode: Option<u32>, owner: Option<Owner>) -> io::Result<()> {
+    if mode.is_none() && owner.is_none() {
+        return Ok(());
+    }
+    Err(io::Error::new(io::ErrorKind::Unsupported, "--chmod and --chown are only available on Unix"))
+}
+
+#[cfg(unix)]
+fn user_id(name: &str) -> Option<u32> {
+    let name = std::ffi::CString::new(name).ok()?;
+    // SAFETY: getpwnam returns null or a record that stays valid until the next call
+    let entry = unsafe { libc::getpwnam(name.as_ptr()) };
+    // SAFETY: checked for null; read before any other passwd lookup
+    (!entry.is_null()).then(|| unsafe { (*entry).pw_uid })
+}
+
+#[cfg(unix)]
+fn group_id(name: &str) -> Option<u32> {
+    let name = std::ffi::CString::new(name).ok()?;
+    // SAFETY: getgrnam returns null or a record that stays valid until the next call
+    let entry = unsafe { libc::getgrnam(name.as_ptr()) };
+    // SAFETY: checked for null; read before any other group lookup
+    (!entry.is_null()).then(|| unsafe { (*entry).gr_gid })
+}
+
+#[cfg(not(unix))]
+fn user_id(_name: &str) -> Option<u32> {
+    None
+}
+
+#[cfg(not(unix))]
+fn group_id(_name: &str) -> Option<u32> {
+    None
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input, Select};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+enum Choice {
+    UseCurrent,
+    Parent,
+    TypePath,
+    Entry(PathBuf, bool),
+}
+
+/// Offer recently used paths as quick picks, falling back to the browser
+pub fn choose_path(prompt: &str, recent: &[PathBuf]) -> Result<PathBuf> {
+    if recent.is_empty() {
+        return pick_path(prompt);
+    }
+
+    let mut labels: Vec<String> = recent
+        .iter()
+        .map(|p| format!("{}{}", icon!("🕘 "), p.display()))
+        .collect();
+    labels.push(icon!("🔍 ", "Browse..."));
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(&labels)
+        .default(0)
+        .interact()?;
+
+    match recent.get(selection) {
+        Some(path) => Ok(path.clone()),
+        None => pick_path(prompt),
+    }
+}
+
+/// Browse the filesystem with a fuzzy finder, descending into directories
+/// until the user picks a file, the current directory, or types a path.
+pub fn pick_path(prompt: &str) -> Result<PathBuf> {
+    let theme = ColorfulTheme::default();
+    let mut current = env::current_dir()?;
+
+    loop {
+        let mut choices = vec![Choice::UseCurrent];
+        if current.parent().is_some() {
+            choices.push(Choice::Parent);
+        }
+        choices.push(Choice::TypePath);
+        choices.extend(list_entries(&current).into_iter().map(|(p, d)| Choice::Entry(p, d)));
+
+        let labels: Vec<String> = choices.iter().map(|c| label(c, &current)).collect();
+        let selection = FuzzySelect::with_theme(&theme)
+            .with_prompt(format!("{} ({})", prompt, current.display()))
+            .items(&labels)
+            .default(0)
+            .interact()?;
+
+        match &choices[selection] {
+            Choice::UseCurrent => return Ok(current),
+            Choice::Parent => {
+                if let Some(parent) = current.parent() {
+                    current = parent.to_path_buf();
+                }
+            }
+            Choice::TypePath => {
+                let typed: String = Input::with_theme(&theme)
+                    .with_prompt(prompt)
+                    .with_initial_text(format!("{}/", current.display()))
+                    .interact_text()?;
+                return Ok(PathBuf::from(typed));
+            }
+            Choice::Entry(path, true) => current = path.clone(),
+            Choice::Entry(path, false) => return Ok(path.clone()),
+        }
+    }
+}
+
+fn label(choice: &Choice, current: &Path) -> String {
+    match choice {
+        Choice::UseCurrent => format!("{}{}", icon!("✅ "), tr!("Use {path}", path = current.display())),
+        Choice::Parent => String::from("⬆️  .."),
+        Choice::TypePath => icon!("✏️  ", "Type a path..."),
+        Choice::Entry(path, is_dir) => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if *is_dir {
+                format!("{}{}/", icon!("📁 "), name)
+            } else {
+                format!("{}{}", icon!("📄 "), name)
+            }
+        }
+    }
+}
+
+/// Directory entries sorted with folders first, then by name
+fn list_entries(dir: &Path) -> Vec<(PathBuf, bool)> {
+    let mut entries: Vec<(PathBuf, bool)> = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir
+            .filter_map(|e| e.ok())
+            .map(|e| {
+                let path = e.path();
+                let is_dir = path.is_dir();
+                (path, is_dir)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
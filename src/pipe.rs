@@ -0,0 +1,52 @@
+use std::{
+    io::{self, Read, Write},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+/// Reader that adds the bytes it reads to a shared progress counter
+pub struct CountingReader<'a, R> {
+    pub inner: R,
+    pub counter: &'a AtomicU64,
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.counter.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+/// Reader that feeds everything it reads to a hasher (when there is one), so
+/// data is hashed on its way through the copy instead of being read twice
+pub struct HashingReader<'a, R> {
+    pub inner: R,
+    pub hasher: Option<&'a mut blake3::Hasher>,
+}
+
+impl<R: Read> Read for HashingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..read]);
+        }
+        Ok(read)
+    }
+}
+
+/// Copy everything from `reader` to `writer`, stopping early on interruption
+pub fn pump(mut reader: impl Read, writer: &mut impl Write, interrupted: &AtomicBool) -> io::Result<u64> {
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut written = 0u64;
+    loop {
+        if interrupted.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "copy interrupted"));
+        }
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(written);
+        }
+        writer.write_all(&buffer[..read])?;
+        written += read as u64;
+    }
+}
@@ -0,0 +1,422 @@
+use crate::ownership::IdMap;
+use clap::ValueEnum;
+use std::{fs, io, path::Path, sync::OnceLock, time::SystemTime};
+
+/// File attributes that `--preserve` carries over from source to destination
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preserve {
+    /// NTFS security descriptor: owner, group, DACL and (with privilege) SACL (Windows)
+    Acl,
+    /// Finder flags, tags, resource fork, ACL and extended attributes, like a Finder copy (macOS)
+    Finder,
+    /// owning user and group, through --usermap and --groupmap (Unix; needs root to give files away)
+    Owner,
+    /// access time: the copy gets the source's, and the source keeps it where reading could not avoid moving it
+    Atime,
+    /// modification time
+    Timestamps,
+    /// permissions; kept unless --no-preserve=mode, which gives copies those of a new file
+    Mode,
+    /// every attribute above that this platform supports (the owner only when running as root)
+    All,
+}
+
+impl Preserve {
+    /// Why this attribute cannot be preserved on the current platform, if it cannot
+    pub fn unsupported(self) -> Option<&'static str> {
+        match self {
+            Preserve::Acl if !cfg!(windows) => Some("--preserve=acl is only available on Windows"),
+            Preserve::Finder if !cfg!(target_os = "macos") => Some("--preserve=finder is only available on macOS"),
+            Preserve::Owner if !cfg!(unix) => Some("--preserve=owner is only available on Unix"),
+            Preserve::Acl
+            | Preserve::Finder
+            | Preserve::Owner
+            | Preserve::Atime
+            | Preserve::Timestamps
+            | Preserve::Mode
+            | Preserve::All => None,
+        }
+    }
+}
+
+/// The attributes to preserve: the mode, and `preserve` with `all` expanded
+/// to every one this platform supports, less those in `no_preserve`
+/// (`--no-preserve`), so `--preserve=all --no-preserve=owner` keeps all but
+/// the owner. Giving copies away to another owner needs root, so `all` only
+/// takes the owner in for root.
+pub fn resolve(preserve: &[Preserve], no_preserve: &[Preserve]) -> Vec<Preserve> {
+    let supported = || {
+        Preserve::value_variants()
+            .iter()
+            .copied()
+            .filter(|attribute| *attribute != Preserve::All && attribute.unsupported().is_none())
+            .filter(|attribute| *attribute != Preserve::Owner || running_as_root())
+    };
+    let mut resolved = Vec::new();
+    for &attribute in std::iter::once(&Preserve::Mode).chain(preserve) {
+        let expanded: Vec<Preserve> = match attribute {
+            Preserve::All => supported().collect(),
+            attribute => vec![attribute],
+        };
+        for attribute in expanded {
+            let removed = no_preserve.contains(&attribute) || no_preserve.contains(&Preserve::All);
+            if !removed && !resolved.contains(&attribute) {
+                resolved.push(attribute);
+            }
+        }
+    }
+    if !resolved.contains(&Preserve::Mode) {
+        // Read now, while no other thread can create a file under the mask umask() swaps in
+        new_file_mode();
+    }
+    resolved
+}
+
+#[cfg(unix)]
+fn running_as_root() -> bool {
+    // SAFETY: geteuid cannot fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(unix))]
+fn running_as_root() -> bool {
+    false
+}
+
+/// How --preserve=owner gives copies their owner
+#[derive(Clone, Copy)]
+pub struct Ownership<'a> {
+    pub users: Option<&'a IdMap>,
+    pub groups: Option<&'a IdMap>,
+    /// Record the owner and mode in an extended attribute instead of setting
+    /// them, for runs without the privilege to (--fake-super)
+    pub fake_super: bool,
+}
+
+/// Apply the requested attributes of `source` to the freshly copied `target`;
+/// `before` is what the walk read of `source`, before the copy read it
+pub fn apply(
+    attributes: &[Preserve],
+    source: &Path,
+    before: Option<&fs::Metadata>,
+    target: &Path,
+    ownership: Ownership,
+) -> io::Result<()> {
+    for attribute in attributes {
+        match attribute {
+            Preserve::Acl => copy_security(source, target)?,
+            Preserve::Finder => copy_finder_metadata(source, target)?,
+            Preserve::Owner => copy_owner(source, target, ownership)?,
+            Preserve::Atime => copy_atime(source, before, target)?,
+            Preserve::Timestamps => copy_modified(source, before, target)?,
+            // The copy already has the source's permissions
+            Preserve::Mode => {}
+            // Expanded by resolve
+            Preserve::All => {}
+        }
+    }
+    Ok(())
+}
+
+fn copy_modified(source: &Path, before: Option<&fs::Metadata>, target: &Path) -> io::Result<()> {
+    let modified = match before {
+        Some(meta) => meta.modified()?,
+        None => fs::metadata(source)?.modified()?,
+    };
+    set_modified(target, modified)
+}
+
+/// Give `target` the permissions a new file gets, in place of the source's
+/// it was copied with (--no-preserve=mode)
+pub fn reset_mode(target: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(target, fs::Permissions::from_mode(new_file_mode()))
+    }
+    #[cfg(not(unix))]
+    {
+        let mut permissions = fs::metadata(target)?.permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(target, permissions)
+    }
+}
+
+/// Mode of a newly created file: 0666 less the umask
+fn new_file_mode() -> u32 {
+    static MODE: OnceLock<u32> = OnceLock::new();
+    *MODE.get_or_init(|| {
+        #[cfg(unix)]
+        {
+            // SAFETY: umask only swaps the process's mask, and the old one is put straight back
+            let mask = unsafe {
+                let mask = libc::umask(0o022);
+                libc::umask(mask);
+                mask
+            };
+            0o666 & !(mask as u32)
+        }
+        #[cfg(not(unix))]
+        0o666
+    })
+}
+
+/// Give `target` the access time `source` had before it was copied, and put
+/// that time back on `source` if reading it moved it (O_NOATIME is only
+/// allowed to the owner). Restoring the source is best effort: it needs the
+/// same ownership, and a source left with a fresh atime does not make the
+/// copy wrong.
+fn copy_atime(source: &Path, before: Option<&fs::Metadata>, target: &Path) -> io::Result<()> {
+    let accessed = match before {
+        Some(meta) => meta.accessed()?,
+        None => fs::metadata(source)?.accessed()?,
+    };
+    set_accessed(target, accessed)?;
+    if fs::metadata(source).and_then(|meta| meta.accessed()).is_ok_and(|now| now != accessed) {
+        let _ = set_accessed(source, accessed);
+    }
+    Ok(())
+}
+
+fn set_accessed(path: &Path, accessed: SystemTime) -> io::Result<()> {
+    open_for_times(path)?.set_times(fs::FileTimes::new().set_accessed(accessed))
+}
+
+/// Give `path` the modification time `modified` (--touch)
+pub fn set_modified(path: &Path, modified: SystemTime) -> io::Result<()> {
+    open_for_times(path)?.set_times(fs::FileTimes::new().set_modified(modified))
+}
+
+fn open_for_times(path: &Path) -> io::Result<fs::File> {
+    // futimens needs no write access, only ownership; Windows wants a handle that may write attributes
+    #[cfg(unix)]
+    return fs::File::open(path);
+    #[cfg(not(unix))]
+    return fs::OpenOptions::new().write(true).open(path);
+}
+
+/// Copy the security descriptor of `source` onto `target`. Setting the owner
+/// and SACL needs backup/restore privileges; without them only the DACL is copied.
+#[cfg(windows)]
+fn copy_security(source: &Path, target: &Path) -> io::Result<()> {
+    use std::{os::windows::ffi::OsStrExt, ptr};
+    use windows_sys::Win32::{
+        Foundation::{ERROR_ACCESS_DENIED, ERROR_PRIVILEGE_NOT_HELD, LocalFree},
+        Security::{
+            Authorization::{GetNamedSecurityInfoW, SE_FILE_OBJECT, SetNamedSecurityInfoW},
+            DACL_SECURITY_INFORMATION, GROUP_SECURITY_INFORMATION, GetSecurityDescriptorControl,
+            OWNER_SECURITY_INFORMATION, PROTECTED_DACL_SECURITY_INFORMATION, SACL_SECURITY_INFORMATION,
+            SE_DACL_PROTECTED, UNPROTECTED_DACL_SECURITY_INFORMATION,
+        },
+    };
+
+    let wide = |path: &Path| -> Vec<u16> { path.as_os_str().encode_wide().chain(Some(0)).collect() };
+    let (source, target) = (wide(source), wide(target));
+
+    let read = |info| {
+        let (mut owner, mut group, mut dacl, mut sacl, mut descriptor) =
+            (ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+        // SAFETY: all out-pointers are valid; the descriptor is freed by the caller
+        let status = unsafe {
+            GetNamedSecurityInfoW(
+                source.as_ptr(), SE_FILE_OBJECT, info,
+                &mut owner, &mut group, &mut dacl, &mut sacl, &mut descriptor,
+            )
+        };
+        (status, owner, group, dacl, sacl, descriptor)
+    };
+
+    let full = OWNER_SECURITY_INFORMATION | GROUP_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION | SACL_SECURITY_INFORMATION;
+    let mut info = full;
+    let mut attempt = read(info);
+    if matches!(attempt.0, ERROR_ACCESS_DENIED | ERROR_PRIVILEGE_NOT_HELD) {
+        info = DACL_SECURITY_INFORMATION;
+        attempt = read(info);
+    }
+    let (status, owner, group, dacl, sacl, descriptor) = attempt;
+    if status != 0 {
+        return Err(io::Error::from_raw_os_error(status as i32));
+    }
+
+    // Keep "disable inheritance" as it was on the source
+    let mut control = 0u16;
+    let mut revision = 0u32;
+    unsafe { GetSecurityDescriptorControl(descriptor, &mut control, &mut revision) };
+    let inheritance = if control & SE_DACL_PROTECTED != 0 {
+        PROTECTED_DACL_SECURITY_INFORMATION
+    } else {
+        UNPROTECTED_DACL_SECURITY_INFORMATION
+    };
+
+    let set = |info| unsafe { SetNamedSecurityInfoW(target.as_ptr(), SE_FILE_OBJECT, info | inheritance, owner, group, dacl, sacl) };
+    let mut status = set(info);
+    if info == full && matches!(status, ERROR_ACCESS_DENIED | ERROR_PRIVILEGE_NOT_HELD) {
+        status = set(DACL_SECURITY_INFORMATION);
+    }
+    unsafe { LocalFree(descriptor) };
+    match status {
+        0 => Ok(()),
+        code => Err(io::Error::from_raw_os_error(code as i32)),
+    }
+}
+
+#[cfg(not(windows))]
+fn copy_security(_source: &Path, _target: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Copy everything but the data with copyfile(3), as Finder does: mode, flags,
+/// timestamps, ACL, and extended attributes (tags, resource fork, Finder info)
+#[cfg(target_os = "macos")]
+fn copy_finder_metadata(source: &Path, target: &Path) -> io::Result<()> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt, ptr};
+
+    let from = CString::new(source.as_os_str().as_bytes())?;
+    let to = CString::new(target.as_os_str().as_bytes())?;
+    // SAFETY: both paths are NUL-terminated; a null state is allowed
+    let status = unsafe { libc::copyfile(from.as_ptr(), to.as_ptr(), ptr::null_mut(), libc::COPYFILE_METADATA) };
+    if status < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn copy_finder_metadata(_source: &Path, _target: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Extended attribute holding the real mode, device and owner of a file
+/// stored with --fake-super, in rsync's format: `100644 0,0 1000:1000`
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+const FAKE_SUPER_ATTR: &str = "user.copro.stat";
+
+/// Real attributes of a file, as --fake-super records them
+struct Stat {
+    mode: u32,
+    device: (u64, u64),
+    uid: u32,
+    gid: u32,
+}
+
+impl Stat {
+    fn encode(&self) -> String {
+        format!("{:o} {},{} {}:{}", self.mode, self.device.0, self.device.1, self.uid, self.gid)
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let mut fields = value.split(' ');
+        let mode = u32::from_str_radix(fields.next()?, 8).ok()?;
+        let (major, minor) = fields.next()?.split_once(',')?;
+        let (uid, gid) = fields.next()?.split_once(':')?;
+        Some(Self {
+            mode,
+            device: (major.parse().ok()?, minor.parse().ok()?),
+            uid: uid.parse().ok()?,
+            gid: gid.parse().ok()?,
+        })
+    }
+}
+
+/// Give `target` the user and group of `source`, mapped to the destination's
+/// IDs. A source stored with --fake-super carries its real attributes along,
+/// which a privileged copy puts back, mode included.
+#[cfg(unix)]
+fn copy_owner(source: &Path, target: &Path, ownership: Ownership) -> io::Result<()> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let stored = read_fake_super(source)?;
+    let restoring = stored.is_some();
+    let mut stat = match stored {
+        Some(stat) => stat,
+        None => {
+            let meta = std::fs::metadata(source)?;
+            Stat { mode: meta.mode(), device: device_numbers(meta.rdev()), uid: meta.uid(), gid: meta.gid() }
+        }
+    };
+    stat.uid = ownership.users.map_or(stat.uid, |map| map.map(stat.uid));
+    stat.gid = ownership.groups.map_or(stat.gid, |map| map.map(stat.gid));
+
+    if ownership.fake_super {
+        return write_fake_super(target, &stat);
+    }
+    std::os::unix::fs::chown(target, Some(stat.uid), Some(stat.gid))?;
+    if restoring {
+        std::fs::set_permissions(target, std::fs::Permissions::from_mode(stat.mode & 0o7777))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn copy_owner(_source: &Path, _target: &Path, _ownership: Ownership) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn device_numbers(rdev: u64) -> (u64, u64) {
+    let rdev = rdev as libc::dev_t;
+    (libc::major(rdev) as u64, libc::minor(rdev) as u64)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn device_numbers(_rdev: u64) -> (u64, u64) {
+    (0, 0)
+}
+
+/// The attributes --fake-super recorded on `path`, if it has them
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn read_fake_super(path: &Path) -> io::Result<Option<Stat>> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let name = CString::new(FAKE_SUPER_ATTR)?;
+    let mut value = [0u8; 128];
+    // SAFETY: both strings are NUL-terminated and the buffer is as long as stated
+    #[cfg(target_os = "linux")]
+    let len = unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), value.as_mut_ptr().cast(), value.len()) };
+    // SAFETY: as above; position and options are unused for ordinary attributes
+    #[cfg(target_os = "macos")]
+    let len = unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), value.as_mut_ptr().cast(), value.len(), 0, 0) };
+    if len < 0 {
+        let e = io::Error::last_os_error();
+        // Missing, or not supported by the filesystem: nothing was recorded
+        return match e.raw_os_error() {
+            Some(libc::ENODATA | libc::ENOTSUP) => Ok(None),
+            #[cfg(target_os = "macos")]
+            Some(libc::ENOATTR) => Ok(None),
+            _ => Err(e),
+        };
+    }
+    Ok(std::str::from_utf8(&value[..len as usize]).ok().and_then(Stat::decode))
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn read_fake_super(_path: &Path) -> io::Result<Option<Stat>> {
+    Ok(None)
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn write_fake_super(path: &Path, stat: &Stat) -> io::Result<()> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let name = CString::new(FAKE_SUPER_ATTR)?;
+    let value = stat.encode();
+    // SAFETY: both strings are NUL-terminated and the value is as long as stated
+    #[cfg(target_os = "linux")]
+    let status = unsafe { libc::setxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0) };
+    // SAFETY: as above; position and options are unused for ordinary attributes
+    #[cfg(target_os = "macos")]
+    let status = unsafe { libc::setxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0, 0) };
+    if status < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn write_fake_super(_path: &Path, _stat: &Stat) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "--fake-super is not available on this system"))
+}
@@ -0,0 +1,53 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+/// The real standard output, kept for `--print0`'s list of copied files while
+/// everything else that would be printed there goes to standard error
+pub struct Print0 {
+    out: fs::File,
+}
+
+impl Print0 {
+    /// Take over standard output and point the process's stdout at stderr, so
+    /// the progress display and messages cannot end up in the list
+    #[cfg(unix)]
+    pub fn take_stdout() -> io::Result<Self> {
+        use std::os::fd::FromRawFd;
+
+        io::stdout().flush()?;
+        // SAFETY: plain descriptor calls; the duplicate is owned by the File below
+        let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+        if saved < 0 || unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { out: unsafe { fs::File::from_raw_fd(saved) } })
+    }
+
+    #[cfg(windows)]
+    pub fn take_stdout() -> io::Result<Self> {
+        use std::os::windows::io::FromRawHandle;
+        use windows_sys::Win32::System::Console::{GetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE, SetStdHandle};
+
+        io::stdout().flush()?;
+        // SAFETY: std looks the handle up on every write, so swapping it redirects stdout
+        let (out, err) = unsafe { (GetStdHandle(STD_OUTPUT_HANDLE), GetStdHandle(STD_ERROR_HANDLE)) };
+        if unsafe { SetStdHandle(STD_OUTPUT_HANDLE, err) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { out: unsafe { fs::File::from_raw_handle(out) } })
+    }
+
+    /// Write `path` followed by a NUL, straight away so consumers can start on it
+    pub fn emit(&mut self, path: &Path) -> io::Result<()> {
+        #[cfg(unix)]
+        let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+        #[cfg(not(unix))]
+        let bytes = path.to_string_lossy().into_owned().into_bytes();
+        self.out.write_all(&bytes)?;
+        self.out.write_all(b"\0")?;
+        self.out.flush()
+    }
+}
@@ -0,0 +1,62 @@
+use crate::state::state_dir;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const MAX_RECENT: usize = 10;
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Source,
+    Destination,
+}
+
+impl Kind {
+    fn file_name(self) -> &'static str {
+        match self {
+            Kind::Source => "recent-sources",
+            Kind::Destination => "recent-destinations",
+        }
+    }
+}
+
+/// Recently used paths of the given kind, most recent first
+pub fn load(kind: Kind) -> Vec<PathBuf> {
+    let Some(file) = state_dir().map(|d| d.join(kind.file_name())) else {
+        return Vec::new();
+    };
+    fs::read_to_string(file)
+        .map(|content| {
+            content
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Move `path` to the front of the recent list, dropping the oldest entries.
+/// History is a convenience, so failures to persist it are ignored.
+pub fn remember(kind: Kind, path: &Path) {
+    let Some(dir) = state_dir() else {
+        return;
+    };
+    let absolute = fs::canonicalize(path)
+        .or_else(|_| env::current_dir().map(|cwd| cwd.join(path)))
+        .unwrap_or_else(|_| path.to_path_buf());
+
+    let mut entries = load(kind);
+    entries.retain(|p| p != &absolute);
+    entries.insert(0, absolute);
+    entries.truncate(MAX_RECENT);
+
+    let content: String = entries
+        .iter()
+        .map(|p| format!("{}\n", p.display()))
+        .collect();
+    if fs::create_dir_all(&dir).is_ok() {
+        let _ = fs::write(dir.join(kind.file_name()), content);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{catalog::Outcome, datepath::Now, throughput::format_eta};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// `--report-csv`: one row per file with its source, destination, bytes,
+/// duration, status and error, written as the run goes so a stopped run
+/// still leaves the rows of the files it reached
+pub struct CsvReport {
+    pub path: PathBuf,
+    out: io::BufWriter<fs::File>,
+}
+
+impl CsvReport {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        writeln!(out, "source,destination,bytes,seconds,status,error")?;
+        Ok(Self { path: path.to_path_buf(), out })
+    }
+
+    pub fn row(&mut self, source: &Path, destination: &Path, bytes: u64, elapsed: Duration, outcome: Outcome) -> io::Result<()> {
+        let (status, error) = match outcome {
+            Outcome::Copied { .. } => ("copied", ""),
+            Outcome::Unstable => ("unstable", "changed while being copied"),
+            Outcome::Skipped(reason) => ("skipped", reason),
+            Outcome::Failed(error) => ("failed", error),
+        };
+        writeln!(
+            self.out,
+            "{},{},{},{:.3},{},{}",
+            field(&source.to_string_lossy()),
+            field(&destination.to_string_lossy()),
+            bytes,
+            elapsed.as_secs_f64(),
+            status,
+            field(error)
+        )
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// `value` as a CSV field, quoted when it holds a separator, a quote or a
+/// line break (RFC 4180)
+fn field(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+/// Width of the throughput chart, and how many columns it is split into
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 180.0;
+const CHART_COLUMNS: usize = 60;
+
+/// A file that ended up failed, for the table of --report-html
+struct Failure {
+    source: String,
+    destination: String,
+    bytes: u64,
+    error: String,
+}
+
+/// `--report-html`: a single self-contained page with the totals of the run,
+/// a chart of its throughput over time and a sortable table of the failed
+/// files. Results are kept in memory and the page is written when the run
+/// ends.
+pub struct HtmlReport {
+    pub path: PathBuf,
+    source: PathBuf,
+    destination: PathBuf,
+    started: Instant,
+    date: String,
+    /// When each copied file finished (seconds into the run), how long it
+    /// took and its size
+    spans: Vec<(f64, f64, u64)>,
+    failures: Vec<Failure>,
+    copied: usize,
+    skipped: usize,
+    unstable: usize,
+    bytes: u64,
+}
+
+impl HtmlReport {
+    pub fn new(path: &Path, source: &Path, destination: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            source: source.to_path_buf(),
+            destination: destination.to_path_buf(),
+            started: Instant::now(),
+            date: Now::local().expand(Path::new("%F %T")).display().to_string(),
+            spans: Vec::new(),
+            failures: Vec::new(),
+            copied: 0,
+            skipped: 0,
+            unstable: 0,
+            bytes: 0,
+        }
+    }
+
+    pub fn row(&mut self, source: &Path, destination: &Path, bytes: u64, elapsed: Duration, outcome: Outcome) {
+        match outcome {
+            Outcome::Copied { .. } => {
+                self.copied += 1;
+                self.bytes += bytes;
+                self.spans.push((self.started.elapsed().as_secs_f64(), elapsed.as_secs_f64(), bytes));
+            }
+            Outcome::Unstable => {
+                self.unstable += 1;
+                self.bytes += bytes;
+            }
+            Outcome::Skipped(_) => self.skipped += 1,
+            Outcome::Failed(error) => self.failures.push(Failure {
+                source: source.to_string_lossy().into_owned(),
+                destination: destination.to_string_lossy().into_owned(),
+                bytes,
+                error: error.to_string(),
+            }),
+        }
+    }
+
+    /// Write the page; `status` is `completed` or why the run stopped
+    pub fn finish(self, status: &str) -> io::Result<()> {
+        let duration = self.started.elapsed();
+        let seconds = duration.as_secs_f64().max(0.001);
+        let mut out = io::BufWriter::new(fs::File::create(&self.path)?);
+        writeln!(out, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>copro report: {}</title>", escape(&self.source.to_string_lossy()))?;
+        writeln!(out, "<style>{STYLE}</style>\n</head>\n<body>")?;
+        writeln!(out, "<h1>copro run report</h1>")?;
+        writeln!(
+            out,
+            "<p class=\"paths\"><code>{}</code> → <code>{}</code><br>started {}</p>",
+            escape(&self.source.to_string_lossy()),
+            escape(&self.destination.to_string_lossy()),
+            self.date
+        )?;
+
+        let class = if status == "completed" && self.failures.is_empty() { "ok" } else { "bad" };
+        writeln!(out, "<div class=\"stats\">")?;
+        let mut stats = vec![
+            ("Status", status.to_string(), class),
+            ("Copied", self.copied.to_string(), ""),
+            ("Skipped", self.skipped.to_string(), ""),
+        ];
+        // Only runs that met a changing source have them
+        if self.unstable > 0 {
+            stats.push(("Unstable", self.unstable.to_string(), "bad"));
+        }
+        stats.extend([
+            ("Failed", self.failures.len().to_string(), if self.failures.is_empty() { "" } else { "bad" }),
+            ("Data", crate::format_size(self.bytes as f64), ""),
+            ("Duration", format_eta(duration), ""),
+            ("Average", format!("{}/s", crate::format_size(self.bytes as f64 / seconds)), ""),
+        ]);
+        for (label, value, class) in stats {
+            writeln!(out, "<div class=\"stat\"><span>{label}</span><b class=\"{class}\">{}</b></div>", escape(&value))?;
+        }
+        writeln!(out, "</div>")?;
+
+        writeln!(out, "<h2>Throughput</h2>")?;
+        self.write_chart(&mut out, seconds)?;
+
+        writeln!(out, "<h2>Failures</h2>")?;
+        if self.failures.is_empty() {
+            writeln!(out, "<p>No file failed.</p>")?;
+        } else {
+            writeln!(out, "<table id=\"failures\">\n<thead><tr>")?;
+            for (column, kind) in [("Source", "text"), ("Destination", "text"), ("Size", "number"), ("Error", "text")] {
+                writeln!(out, "<th data-kind=\"{kind}\">{column}</th>")?;
+            }
+            writeln!(out, "</tr></thead>\n<tbody>")?;
+            for failure in &self.failures {
+                writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td data-value=\"{}\">{}</td><td>{}</td></tr>",
+                    escape(&failure.source),
+                    escape(&failure.destination),
+                    failure.bytes,
+                    crate::format_size(failure.bytes as f64),
+                    escape(&failure.error)
+                )?;
+            }
+            writeln!(out, "</tbody>\n</table>\n<script>{SCRIPT}</script>")?;
+        }
+        writeln!(out, "</body>\n</html>")?;
+        out.flush()
+    }
+
+    /// An SVG area chart of the bytes copied per second. Each file's bytes
+    /// are spread over the time it took, so files copied in parallel add up
+    /// and a large file shows as a plateau rather than a spike at its end.
+    fn write_chart(&self, out: &mut impl Write, seconds: f64) -> io::Result<()> {
+        let columns = CHART_COLUMNS.min(seconds.ceil() as usize).max(1);
+        let width = seconds / columns as f64;
+        let mut totals = vec![0.0; columns];
+        for &(end, took, bytes) in &self.spans {
+            let start = (end - took).max(0.0);
+            if took <= 0.0 {
+                totals[((end / width) as usize).min(columns - 1)] += bytes as f64;
+                continue;
+            }
+            let first = ((start / width) as usize).min(columns - 1);
+            let last = ((end / width) as usize).min(columns - 1);
+            for (column, total) in totals.iter_mut().enumerate().take(last + 1).skip(first) {
+                let from = start.max(column as f64 * width);
+                let to = end.min((column + 1) as f64 * width);
+                *total += bytes as f64 * (to - from).max(0.0) / took;
+            }
+        }
+        let rates: Vec<f64> = totals.iter().map(|total| total / width).collect();
+        let peak = rates.iter().copied().fold(0.0, f64::max);
+        if peak <= 0.0 {
+            return writeln!(out, "<p>No data was copied.</p>");
+        }
+
+        let step = CHART_WIDTH / columns as f64;
+        let mut points = format!("0,{CHART_HEIGHT}");
+        for (column, rate) in rates.iter().enumerate() {
+            let y = CHART_HEIGHT - rate / peak * (CHART_HEIGHT - 10.0);
+            points.push_str(&format!(" {:.1},{y:.1} {:.1},{y:.1}", column as f64 * step, (column + 1) as f64 * step));
+        }
+        points.push_str(&format!(" {CHART_WIDTH},{CHART_HEIGHT}"));
+        writeln!(
+            out,
+            "<svg class=\"chart\" viewBox=\"0 0 {CHART_WIDTH} {}\" role=\"img\" aria-label=\"Throughput over time\">",
+            CHART_HEIGHT + 20.0
+        )?;
+        writeln!(out, "<polygon points=\"{points}\"/>")?;
+        writeln!(out, "<line x1=\"0\" y1=\"{CHART_HEIGHT}\" x2=\"{CHART_WIDTH}\" y2=\"{CHART_HEIGHT}\"/>")?;
+        writeln!(out, "<text x=\"4\" y=\"14\">{}/s</text>", crate::format_size(peak))?;
+        writeln!(out, "<text x=\"0\" y=\"{}\">0s</text>", CHART_HEIGHT + 16.0)?;
+        writeln!(
+            out,
+            "<text x=\"{CHART_WIDTH}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            CHART_HEIGHT + 16.0,
+            format_eta(Duration::from_secs_f64(seconds))
+        )?;
+        writeln!(out, "</svg>")
+    }
+}
+
+const STYLE: &str = "
+body { font: 14px/1.5 system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; color: #222; }
+h1 { font-size: 1.5rem; margin-bottom: .25rem; }
+h2 { font-size: 1.1rem; margin-top: 2rem; }
+.paths { color: #555; }
+.stats { display: flex; flex-wrap: wrap; gap: .75rem; }
+.stat { border: 1px solid #ddd; border-radius: 6px; padding: .5rem .9rem; min-width: 7rem; }
+.stat span { display: block; color: #666; font-size: .8rem; }
+.stat b { font-size: 1.2rem; }
+.ok { color: #1a7f37; }
+.bad { color: #cf222e; }
+.chart { width: 100%; height: auto; }
+.chart polygon { fill: #54aeff; fill-opacity: .5; stroke: #0969da; }
+.chart line { stroke: #999; }
+.chart text { font-size: 11px; fill: #555; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: .3rem .5rem; border-bottom: 1px solid #eee; vertical-align: top; word-break: break-all; }
+th { cursor: pointer; user-select: none; background: #f6f8fa; }
+th[aria-sort=ascending]::after { content: ' ▲'; }
+th[aria-sort=descending]::after { content: ' ▼'; }
+";
+
+/// Sort the failures table by the clicked column, toggling the direction
+const SCRIPT: &str = "
+document.querySelectorAll('#failures th').forEach((th, column) => th.addEventListener('click', () => {
+  const body = th.closest('table').tBodies[0];
+  const ascending = th.getAttribute('aria-sort') !== 'ascending';
+  th.parentNode.querySelectorAll('th').forEach(other => other.removeAttribute('aria-sort'));
+  th.setAttribute('aria-sort', ascending ? 'ascending' : 'descending');
+  const key = row => th.dataset.kind === 'number'
+    ? Number(row.cells[column].dataset.value) : row.cells[column].textContent.toLowerCase();
+  const rows = Array.from(body.rows).sort((a, b) => {
+    const [x, y] = [key(a), key(b)];
+    return (x < y ? -1 : x > y ? 1 : 0) * (ascending ? 1 : -1);
+  });
+  rows.forEach(row => body.appendChild(row));
+}));
+";
+
+/// `text` with the characters HTML gives a meaning escaped
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,252 @@
+use crate::{
+    crypto::Crypto,
+    format_size, lock,
+    manifest::{hash_file, EntryResult, Manifest},
+    temp_path,
+};
+use anyhow::{anyhow, Context, Result};
+use console::style;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64},
+};
+
+/// Arguments of `copro restore`
+#[derive(clap::Args, Clone)]
+pub struct RestoreArgs {
+    /// backup or snapshot written by copro
+    backup: PathBuf,
+
+    /// where to put the files back
+    target: PathBuf,
+
+    /// manifest written with --manifest: restore only the files it lists, checking each against
+    /// its recorded hash first
+    #[arg(long, value_name = "FILE")]
+    manifest: Option<PathBuf>,
+
+    /// decrypt .age files with the identities in this file, dropping the extension
+    #[arg(long, value_name = "FILE")]
+    identity: Option<PathBuf>,
+
+    /// replace files that already exist in the target (by default they are left alone)
+    #[arg(long)]
+    overwrite: bool,
+
+    /// list what would be restored without writing anything
+    #[arg(long)]
+    list_only: bool,
+
+    /// also list every file restored
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// A file of the backup to put back
+struct Item {
+    from: PathBuf,
+    /// Path below the backup root, which is also its path below the target
+    relative: PathBuf,
+    /// BLAKE3 the manifest recorded for it
+    hash: Option<String>,
+}
+
+/// Copy the files of a backup back to a target. Returns whether every file
+/// was restored.
+pub fn run(args: &RestoreArgs) -> Result<bool> {
+    let crypto = args
+        .identity
+        .as_deref()
+        .map(Crypto::decrypt)
+        .transpose()
+        .map_err(|e| anyhow!("could not read identities: {e}"))?;
+    let items = match &args.manifest {
+        Some(path) => {
+            let manifest = Manifest::load(path).with_context(|| format!("could not read manifest {}", path.display()))?;
+            from_manifest(manifest, &args.backup)
+        }
+        None => {
+            let mut items = Vec::new();
+            collect(&args.backup, Path::new(""), &mut items)
+                .with_context(|| format!("could not read {}", args.backup.display()))?;
+            items
+        }
+    };
+
+    println!("{}{} {}", icon!("⏮️  "),
+        style(tr!("RESTORING")).cyan().bold(),
+        tr!("{count} files from {backup} to {target}",
+            count = style(items.len()).yellow(),
+            backup = style(args.backup.display()).white(),
+            target = style(args.target.display()).white()
+        )
+    );
+
+    let (mut restored, mut bytes, mut kept, mut encrypted, mut problems) = (0usize, 0u64, 0usize, 0usize, 0usize);
+    for item in &items {
+        let decrypt = crypto.as_ref().filter(|_| is_encrypted(&item.from));
+        let mut to = args.target.join(&item.relative);
+        if let Some(crypto) = decrypt {
+            to = crypto.output_path(to);
+        } else if is_encrypted(&item.from) {
+            encrypted += 1;
+        }
+
+        let problem = if let Some(expected) = &item.hash {
+            match hash_file(&item.from) {
+                Ok(actual) if actual == *expected => None,
+                Ok(_) => Some(String::from(tr!("does not match the manifest"))),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Some(String::from(tr!("missing from the backup"))),
+                Err(e) => Some(e.to_string()),
+            }
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            problems += 1;
+            println!("{} {} ({})", style(icon!("❌ ", "Damaged:")).red().bold(), style(item.from.display()).white(), style(problem).red());
+            continue;
+        }
+
+        if !args.overwrite && fs::symlink_metadata(&to).is_ok() {
+            kept += 1;
+            if args.verbose {
+                println!("{} {} ({})",
+                    style(icon!("⏭️  ", "Skipped:")).dim(),
+                    style(to.display()).white(),
+                    tr!("already exists; --overwrite replaces it")
+                );
+            }
+            continue;
+        }
+
+        if args.list_only {
+            println!("{} {} → {}", style(icon!("📄 ", "Would restore:")).cyan(), style(item.from.display()).white(), style(to.display()).white());
+            restored += 1;
+            continue;
+        }
+
+        match restore(&item.from, &to, decrypt) {
+            Ok(size) => {
+                restored += 1;
+                bytes += size;
+                if args.verbose {
+                    println!("{} {}", style(icon!("✅ ", "Restored:")).green().bold(), style(to.display()).white());
+                }
+            }
+            Err(e) => {
+                problems += 1;
+                println!("{} {} ({})", style(icon!("❌ ", "Failed:")).red().bold(), style(to.display()).white(), style(e).red());
+            }
+        }
+    }
+
+    if kept > 0 {
+        println!("{}{}", icon!("💡 "),
+            tr!("{count} files already in the target were left alone (--overwrite replaces them)", count = style(kept).yellow())
+        );
+    }
+    if encrypted > 0 {
+        println!("{}{}", icon!("🔒 "),
+            tr!("{count} files are still encrypted; pass --identity to decrypt them", count = style(encrypted).yellow())
+        );
+    }
+    if args.list_only {
+        println!("{}{}", icon!("📋 "), tr!("{count} files would be restored", count = style(restored).cyan().bold()));
+    } else if problems == 0 {
+        println!("{}{} {}", icon!("🎉 "),
+            style(tr!("RESTORED!")).green().bold(),
+            tr!("{count} files ({size}) to {target}",
+                count = style(restored).cyan().bold(),
+                size = format_size(bytes as f64),
+                target = style(args.target.display()).white()
+            )
+        );
+    } else {
+        println!("{}{} {}", icon!("⚠️  "),
+            style(tr!("INCOMPLETE!")).red().bold(),
+            tr!("{count} files could not be restored", count = style(problems).red().bold())
+        );
+    }
+    Ok(problems == 0)
+}
+
+/// The files a manifest records as copied into `backup`. Entries written to
+/// other destinations (--also-to) are left out.
+fn from_manifest(manifest: Manifest, backup: &Path) -> Vec<Item> {
+    manifest
+        .entries
+        .into_iter()
+        .filter(|entry| entry.result == EntryResult::Copied)
+        .filter_map(|entry| {
+            let relative = entry.destination.strip_prefix(&manifest.destination).ok()?.to_path_buf();
+            // A single file copied to a file path: the backup names the file itself
+            let (from, relative) = if relative.as_os_str().is_empty() {
+                (backup.to_path_buf(), PathBuf::from(entry.destination.file_name()?))
+            } else {
+                (backup.join(&relative), relative)
+            };
+            Some(Item { from, relative, hash: entry.hash })
+        })
+        .collect()
+}
+
+/// Every file below `dir`, leaving out copro's own sidecars: the `.part`
+/// checkpoints of --resume, the `.~N~` versions kept by --backups and the
+/// lock file a run left behind
+fn collect(dir: &Path, relative: &Path, items: &mut Vec<Item>) -> io::Result<()> {
+    if !fs::metadata(dir)?.is_dir() {
+        let name = dir.file_name().map(PathBuf::from).unwrap_or_default();
+        items.push(Item { from: dir.to_path_buf(), relative: name, hash: None });
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let relative = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect(&path, &relative, items)?;
+        } else if !is_sidecar(&path) {
+            items.push(Item { from: path, relative, hash: None });
+        }
+    }
+    Ok(())
+}
+
+fn is_sidecar(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    if name.ends_with(".part") || name == lock::NAME {
+        return true;
+    }
+    name.strip_suffix('~')
+        .and_then(|rest| rest.rsplit_once(".~"))
+        .is_some_and(|(_, version)| !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn is_encrypted(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "age")
+}
+
+/// Write `from` to `to` through a temporary file, decrypting it on the way
+/// when `crypto` is given; returns the bytes written
+fn restore(from: &Path, to: &Path, crypto: Option<&Crypto>) -> io::Result<u64> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = temp_path(to);
+    let written = match crypto {
+        Some(crypto) => crypto.transform(from, &temp, &AtomicU64::new(0), &AtomicBool::new(false)),
+        None => fs::copy(from, &temp),
+    };
+    match written.and_then(|written| fs::rename(&temp, to).map(|()| written)) {
+        Ok(written) => Ok(written),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
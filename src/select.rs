@@ -0,0 +1,234 @@
+use crate::{format_size, walk::FileEntry};
+use anyhow::Result;
+use console::{style, Key, Term};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// A directory or file of the scanned tree. Nodes are stored depth first, so
+/// the files below a node are the contiguous range `files` of the sorted list.
+struct Node {
+    name: String,
+    depth: usize,
+    size: u64,
+    files: Range<usize>,
+    children: Vec<usize>,
+    is_dir: bool,
+    expanded: bool,
+}
+
+/// Whether all, some or none of the files below a node are selected
+#[derive(PartialEq)]
+enum Mark {
+    All,
+    Some,
+    None,
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    checked: Vec<bool>,
+    sizes: Vec<u64>,
+}
+
+impl Tree {
+    /// Build the tree from files sorted by their path relative to the source
+    fn new(relative: &[PathBuf], sizes: Vec<u64>) -> Self {
+        let mut tree = Tree { nodes: Vec::new(), roots: Vec::new(), checked: vec![true; relative.len()], sizes };
+        // The directories leading to the previous file, by name
+        let mut open: Vec<(String, usize)> = Vec::new();
+        for (index, path) in relative.iter().enumerate() {
+            let parts: Vec<String> = path.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+            let (dirs, name) = parts.split_at(parts.len().saturating_sub(1));
+            let common = open.iter().zip(dirs).take_while(|((open, _), dir)| open == *dir).count();
+            open.truncate(common);
+            for dir in &dirs[common..] {
+                let node = tree.add(dir.clone(), open.last().map(|(_, node)| *node), index, true);
+                open.push((dir.clone(), node));
+            }
+            let name = name.first().cloned().unwrap_or_default();
+            tree.add(name, open.last().map(|(_, node)| *node), index, false);
+            for (_, node) in &open {
+                tree.nodes[*node].files.end = index + 1;
+                tree.nodes[*node].size += tree.sizes[index];
+            }
+        }
+        tree
+    }
+
+    fn add(&mut self, name: String, parent: Option<usize>, file: usize, is_dir: bool) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name,
+            depth: parent.map_or(0, |p| self.nodes[p].depth + 1),
+            size: if is_dir { 0 } else { self.sizes[file] },
+            files: file..file + 1,
+            children: Vec::new(),
+            is_dir,
+            expanded: false,
+        });
+        match parent {
+            Some(parent) => self.nodes[parent].children.push(id),
+            None => self.roots.push(id),
+        }
+        id
+    }
+
+    fn mark(&self, node: usize) -> Mark {
+        let checked = &self.checked[self.nodes[node].files.clone()];
+        if checked.iter().all(|&c| c) {
+            Mark::All
+        } else if checked.iter().any(|&c| c) {
+            Mark::Some
+        } else {
+            Mark::None
+        }
+    }
+
+    /// Select everything below `node`, or nothing if it was all selected
+    fn toggle(&mut self, node: usize) {
+        let select = self.mark(node) != Mark::All;
+        let files = self.nodes[node].files.clone();
+        self.checked[files].fill(select);
+    }
+
+    /// Nodes on screen: the roots, and the children of expanded directories
+    fn visible(&self) -> Vec<usize> {
+        fn walk(tree: &Tree, nodes: &[usize], out: &mut Vec<usize>) {
+            for &node in nodes {
+                out.push(node);
+                if tree.nodes[node].expanded {
+                    walk(tree, &tree.nodes[node].children, out);
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(self, &self.roots, &mut out);
+        out
+    }
+
+    fn parent(&self, node: usize) -> Option<usize> {
+        self.nodes.iter().position(|n| n.children.contains(&node))
+    }
+}
+
+/// Let the user deselect parts of the scanned tree before copying. `relative`
+/// gives the path of a file as shown in the tree. Returns the files still
+/// selected, or `None` when the user cancels.
+pub fn choose(files: Vec<FileEntry>, relative: impl Fn(&Path) -> PathBuf) -> Result<Option<Vec<FileEntry>>> {
+    if files.is_empty() {
+        return Ok(Some(files));
+    }
+    let mut files: Vec<(PathBuf, FileEntry)> = files.into_iter().map(|file| (relative(&file.path), file)).collect();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let paths: Vec<PathBuf> = files.iter().map(|(path, _)| path.clone()).collect();
+    let mut tree = Tree::new(&paths, files.iter().map(|(_, file)| file.size()).collect());
+
+    let term = Term::stderr();
+    term.hide_cursor()?;
+    let result = interact(&term, &mut tree);
+    term.show_cursor()?;
+
+    Ok(result?.then(|| {
+        files
+            .into_iter()
+            .zip(&tree.checked)
+            .filter(|(_, checked)| **checked)
+            .map(|((_, file), _)| file)
+            .collect()
+    }))
+}
+
+/// Run the key loop; returns whether the selection was confirmed
+fn interact(term: &Term, tree: &mut Tree) -> Result<bool> {
+    let mut cursor = 0;
+    let mut top = 0;
+    let mut drawn = 0;
+    loop {
+        let visible = tree.visible();
+        cursor = cursor.min(visible.len().saturating_sub(1));
+        let (height, width) = term.size();
+        let rows = (height as usize).saturating_sub(4).max(3);
+        if cursor < top {
+            top = cursor;
+        } else if cursor >= top + rows {
+            top = cursor + 1 - rows;
+        }
+
+        let mut lines = vec![format!("{} ↑/↓ move, space toggle, →/← open/close, enter copy, esc cancel",
+            style(icon!("🌳 ", "Select what to copy:")).cyan().bold()
+        )];
+        for (row, &node) in visible.iter().enumerate().skip(top).take(rows) {
+            lines.push(line(tree, node, row == cursor));
+        }
+        let selected = tree.checked.iter().filter(|&&c| c).count();
+        let selected_bytes: u64 = tree.checked.iter().zip(&tree.sizes).filter(|(c, _)| **c).map(|(_, s)| s).sum();
+        lines.push(format!("   {} of {} files, {} selected",
+            style(selected).yellow(),
+            tree.checked.len(),
+            style(format_size(selected_bytes as f64)).green()
+        ));
+
+        // Wrapped lines would throw off the redraw
+        let lines: Vec<_> = lines.iter().map(|l| console::truncate_str(l, (width as usize).saturating_sub(1), "…")).collect();
+        term.clear_last_lines(drawn)?;
+        term.write_line(&lines.join("\n"))?;
+        drawn = lines.len();
+
+        let Some(&node) = visible.get(cursor) else {
+            return Ok(false);
+        };
+        match term.read_key()? {
+            Key::ArrowUp | Key::Char('k') => cursor = cursor.saturating_sub(1),
+            Key::ArrowDown | Key::Char('j') => cursor += 1,
+            Key::PageUp => cursor = cursor.saturating_sub(rows),
+            Key::PageDown => cursor += rows,
+            Key::Home => cursor = 0,
+            Key::End => cursor = visible.len(),
+            Key::Char(' ') => tree.toggle(node),
+            Key::ArrowRight | Key::Char('l') if tree.nodes[node].is_dir => tree.nodes[node].expanded = true,
+            Key::ArrowLeft | Key::Char('h') => {
+                if tree.nodes[node].expanded {
+                    tree.nodes[node].expanded = false;
+                } else if let Some(parent) = tree.parent(node) {
+                    cursor = tree.visible().iter().position(|&n| n == parent).unwrap_or(cursor);
+                }
+            }
+            Key::Enter => {
+                term.clear_last_lines(drawn)?;
+                return Ok(true);
+            }
+            Key::Escape | Key::Char('q') => {
+                term.clear_last_lines(drawn)?;
+                return Ok(false);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn line(tree: &Tree, node: usize, current: bool) -> String {
+    let entry = &tree.nodes[node];
+    let check = match tree.mark(node) {
+        Mark::All => style("[x]").green(),
+        Mark::Some => style("[~]").yellow(),
+        Mark::None => style("[ ]").dim(),
+    };
+    let arrow = match (entry.is_dir, entry.expanded) {
+        (false, _) => " ",
+        (true, false) => "▸",
+        (true, true) => "▾",
+    };
+    let name = if entry.is_dir { format!("{}/", entry.name) } else { entry.name.clone() };
+    let name = if current { style(name).reverse() } else { style(name).white() };
+    format!("{} {}{} {} {} {}",
+        if current { ">" } else { " " },
+        "  ".repeat(entry.depth),
+        check,
+        arrow,
+        name,
+        style(format_size(entry.size as f64)).dim()
+    )
+}
@@ -0,0 +1,79 @@
+use crate::status::Status;
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+/// How long a client may take to send its request before it is dropped, so
+/// a stuck one does not hold up the others
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Answer HTTP requests on `address` with the state of the run, as JSON, for
+/// dashboards and scripts that poll a long copy. `GET /` and `GET /progress`
+/// both return it, and with the `metrics` feature `GET /metrics` returns it
+/// for Prometheus; anything else is a 404. Requests are handled one at a
+/// time on a background thread, which ends with the process.
+pub fn serve(address: SocketAddr, status: Status) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = answer(stream, &status);
+        }
+    });
+    Ok(())
+}
+
+fn answer(mut stream: TcpStream, status: &Status) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // The headers are not needed, but are read so the client sees its request taken
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+    let json = "application/json";
+    let (code, kind, body) = match (method, path) {
+        ("GET" | "HEAD", "/" | "/progress") => ("200 OK", json, serde_json::to_string(&status.snapshot()).map_err(io::Error::other)? + "\n"),
+        #[cfg(feature = "metrics")]
+        ("GET" | "HEAD", "/metrics") => ("200 OK", "text/plain; version=0.0.4", metrics(&status.snapshot())),
+        ("GET" | "HEAD", _) => ("404 Not Found", json, String::from("{\"error\":\"not found\"}\n")),
+        _ => ("405 Method Not Allowed", json, String::from("{\"error\":\"method not allowed\"}\n")),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {code}\r\nContent-Type: {kind}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    if method != "HEAD" {
+        stream.write_all(body.as_bytes())?;
+    }
+    stream.flush()
+}
+
+/// The snapshot in the Prometheus text format: counters of what has been
+/// copied and what failed, and gauges of the totals and the speed
+#[cfg(feature = "metrics")]
+fn metrics(snapshot: &crate::status::Snapshot) -> String {
+    let elapsed = snapshot.elapsed_seconds.max(1) as f64;
+    let metrics: [(&str, &str, &str, f64); 7] = [
+        ("copro_bytes_copied_total", "counter", "Bytes copied so far", snapshot.bytes_done as f64),
+        ("copro_files_copied_total", "counter", "Files copied so far", snapshot.files_done as f64),
+        ("copro_errors_total", "counter", "Files that could not be copied, less those a retry copied", snapshot.errors as f64),
+        ("copro_bytes", "gauge", "Bytes to copy in this run", snapshot.bytes_total as f64),
+        ("copro_files", "gauge", "Files to copy in this run", snapshot.files_total as f64),
+        ("copro_throughput_bytes_per_second", "gauge", "Current copy speed", snapshot.rate),
+        ("copro_throughput_files_per_second", "gauge", "Files copied per second on average", snapshot.files_done as f64 / elapsed),
+    ];
+    metrics
+        .iter()
+        .map(|(name, kind, help, value)| format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"))
+        .collect()
+}
@@ -0,0 +1,36 @@
+use std::{env, fs, path::PathBuf, time::UNIX_EPOCH};
+
+/// Per-user directory for copro's persistent state (history, logs, caches).
+///
+/// Follows `$XDG_STATE_HOME` on Unix and `%LOCALAPPDATA%` on Windows.
+pub fn state_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_STATE_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("copro"));
+    }
+    if cfg!(windows) {
+        return env::var_os("LOCALAPPDATA").map(|d| PathBuf::from(d).join("copro"));
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state/copro"))
+}
+
+/// Per-user directory for copro's configuration (output templates).
+///
+/// Follows `$XDG_CONFIG_HOME` on Unix and `%APPDATA%` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("copro"));
+    }
+    if cfg!(windows) {
+        return env::var_os("APPDATA").map(|d| PathBuf::from(d).join("copro"));
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/copro"))
+}
+
+/// Modification time in nanoseconds since the Unix epoch, as recorded in
+/// state files to tell whether a file changed since
+pub fn mtime_nanos(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos() as u64)
+}
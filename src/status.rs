@@ -0,0 +1,172 @@
+// Without a status signal on this platform nothing reads the status
+#![cfg_attr(
+    not(any(
+        target_os = "macos",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "linux",
+        target_os = "android"
+    )),
+    allow(dead_code)
+)]
+
+use crate::{
+    format_rate, format_size,
+    throughput::{format_eta, Throughput},
+};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Live counters of a run, for a status line printed on request
+pub struct Status {
+    pub current: Arc<AtomicUsize>,
+    pub total: Arc<AtomicUsize>,
+    pub bytes: Arc<AtomicU64>,
+    pub total_bytes: Arc<AtomicU64>,
+    pub start_time: Instant,
+    pub current_file: Arc<Mutex<Option<PathBuf>>>,
+    pub throughput: Arc<Mutex<Throughput>>,
+    /// Files failed so far, and the latest of them with its error
+    pub failures: Arc<AtomicUsize>,
+    pub last_failure: Arc<Mutex<Option<Failure>>>,
+    /// The progress display's drawing lock, so the line is not written into a frame
+    pub drawing: Arc<Mutex<()>>,
+}
+
+/// A file that could not be copied
+#[derive(Serialize, Clone)]
+pub struct Failure {
+    pub file: PathBuf,
+    pub error: String,
+}
+
+/// The state of a run at one moment, as --serve-progress hands it out
+#[derive(Serialize)]
+pub struct Snapshot {
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub percent: usize,
+    /// Bytes per second: smoothed, or the average so far until there is enough to smooth
+    pub rate: f64,
+    pub eta_seconds: Option<u64>,
+    pub elapsed_seconds: u64,
+    pub current_file: Option<PathBuf>,
+    pub errors: usize,
+    pub last_error: Option<Failure>,
+}
+
+impl Status {
+    pub fn snapshot(&self) -> Snapshot {
+        let current = self.current.load(Ordering::Relaxed);
+        let total = self.total.load(Ordering::Relaxed);
+        let total_bytes = self.total_bytes.load(Ordering::Relaxed);
+        let bytes = self.bytes.load(Ordering::Relaxed).min(total_bytes);
+        let percent = match (total_bytes, total) {
+            (0, 0) => 0,
+            (0, total) => current * 100 / total,
+            (total_bytes, _) => (bytes as u128 * 100 / total_bytes as u128) as usize,
+        };
+        let (rate, left) = match self.throughput.lock() {
+            Ok(mut throughput) => (throughput.sample(bytes), throughput.eta(total_bytes - bytes)),
+            Err(_) => (None, None),
+        };
+        // The average so far, until there is a smoothed rate
+        let rate = rate.unwrap_or_else(|| bytes as f64 / self.start_time.elapsed().as_secs_f64().max(0.001));
+        Snapshot {
+            files_done: current,
+            files_total: total,
+            bytes_done: bytes,
+            bytes_total: total_bytes,
+            percent,
+            rate,
+            eta_seconds: left.filter(|_| total_bytes > bytes).map(|left| left.as_secs_f64().round() as u64),
+            elapsed_seconds: self.start_time.elapsed().as_secs(),
+            current_file: self.current_file.lock().ok().and_then(|f| f.clone()),
+            errors: self.failures.load(Ordering::Relaxed),
+            last_error: self.last_failure.lock().ok().and_then(|f| f.clone()),
+        }
+    }
+
+    /// One line: files done, bytes done, percent, rate, ETA and the file being copied
+    pub fn line(&self) -> String {
+        let snapshot = self.snapshot();
+        let eta = snapshot.eta_seconds.map(|left| format!(", {} left", format_eta(Duration::from_secs(left))));
+        format!(
+            "copro: {}/{} files, {} of {} ({}%) at {}{}{}",
+            snapshot.files_done,
+            snapshot.files_total,
+            format_size(snapshot.bytes_done as f64),
+            format_size(snapshot.bytes_total as f64),
+            snapshot.percent,
+            format_rate(snapshot.rate),
+            eta.unwrap_or_default(),
+            snapshot.current_file.map(|f| format!(", copying {}", f.display())).unwrap_or_default()
+        )
+    }
+}
+
+/// The signal that asks for a status line: SIGINFO, sent by the terminal's
+/// status key (Ctrl+T) on macOS and the BSDs, and SIGUSR1 on Linux, as with dd
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+const SIGNAL: std::ffi::c_int = signal_hook::consts::SIGINFO;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SIGNAL: std::ffi::c_int = signal_hook::consts::SIGUSR1;
+
+/// Print the status line to stderr whenever the status signal arrives,
+/// without disturbing the run
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "linux",
+    target_os = "android"
+))]
+pub fn listen(status: Status) {
+    use signal_hook::iterator::Signals;
+
+    let Ok(mut signals) = Signals::new([SIGNAL]) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let progress = console::Term::stdout();
+        for _ in signals.forever() {
+            let line = status.line();
+            // On a terminal the progress line is cleared first; the next frame redraws it below
+            let _drawing = status.drawing.lock();
+            if progress.is_term() && !crate::accessible() {
+                let _ = progress.clear_line();
+            }
+            eprintln!("{}", line);
+        }
+    });
+}
+
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "linux",
+    target_os = "android"
+)))]
+pub fn listen(_status: Status) {}
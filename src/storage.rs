@@ -0,0 +1,92 @@
+use std::{path::Path, thread};
+
+/// What holds a path, as far as reading it in parallel goes
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+enum Drive {
+    /// A spinning disk: parallel reads only add seeks
+    Rotational,
+    /// A SATA or SAS SSD, which a few queued reads keep busy
+    Solid,
+    /// An NVMe drive, with queues deep enough for a thread per core
+    Nvme,
+    /// Network, memory or virtual storage, or a platform without the information
+    Unknown,
+}
+
+/// Threads to read `paths` with when none was asked for: one if any of
+/// them is on a spinning disk, at most four on a SATA SSD, and one per CPU
+/// otherwise
+pub fn workers(paths: &[&Path]) -> usize {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let drives: Vec<Drive> = paths.iter().map(|path| drive(path)).collect();
+    if drives.contains(&Drive::Rotational) {
+        1
+    } else if drives.contains(&Drive::Solid) {
+        cores.min(4)
+    } else {
+        cores
+    }
+}
+
+/// The kind of drive `path` is stored on, from the block device behind its
+/// filesystem in /sys
+#[cfg(target_os = "linux")]
+fn drive(path: &Path) -> Drive {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(meta) = std::fs::metadata(path) else {
+        return Drive::Unknown;
+    };
+    let dev = meta.dev();
+    let device = format!("/sys/dev/block/{}:{}", libc::major(dev), libc::minor(dev));
+    match std::fs::canonicalize(device) {
+        Ok(device) => block_drive(&device),
+        // No block device: NFS, FUSE, tmpfs, overlay
+        Err(_) => Drive::Unknown,
+    }
+}
+
+/// The kind of drive behind a block device directory in /sys. Partitions
+/// take after their disk, and device-mapper and md devices after the disks
+/// they are built on.
+#[cfg(target_os = "linux")]
+fn block_drive(device: &Path) -> Drive {
+    let disk = match device.join("partition").exists() {
+        true => device.parent().unwrap_or(device),
+        false => device,
+    };
+    let name = disk.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    // A loop device is as fast as whatever holds its backing file, and virtual
+    // machine disks claim to spin unless the host says otherwise
+    if ["loop", "zram", "ram", "vd", "xvd"].iter().any(|prefix| name.starts_with(prefix)) {
+        return Drive::Unknown;
+    }
+
+    let slaves: Vec<Drive> = std::fs::read_dir(disk.join("slaves"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|slave| std::fs::canonicalize(slave.path()).ok())
+        .map(|slave| block_drive(&slave))
+        .collect();
+    if !slaves.is_empty() {
+        // The slowest member decides
+        return [Drive::Rotational, Drive::Solid, Drive::Unknown, Drive::Nvme]
+            .into_iter()
+            .find(|kind| slaves.contains(kind))
+            .unwrap_or(Drive::Unknown);
+    }
+
+    match std::fs::read_to_string(disk.join("queue/rotational")).as_deref().map(str::trim) {
+        Ok("1") => Drive::Rotational,
+        Ok(_) if name.starts_with("nvme") => Drive::Nvme,
+        Ok("0") => Drive::Solid,
+        _ => Drive::Unknown,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn drive(_path: &Path) -> Drive {
+    Drive::Unknown
+}
@@ -0,0 +1,79 @@
+use crate::state::mtime_nanos;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The source a destination file was last synced from, as it was then
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct Snapshot {
+    source: PathBuf,
+    size: u64,
+    /// Source modification time in nanoseconds since the Unix epoch
+    modified: u64,
+}
+
+impl Snapshot {
+    fn of(source: &Path, meta: &fs::Metadata) -> Self {
+        Self { source: absolute(source), size: meta.len(), modified: mtime_nanos(meta) }
+    }
+}
+
+/// What a previous --update run left in the destination (--sync-state), so
+/// files whose source has not changed since are skipped without looking at
+/// the destination at all. It trusts the destination not to be changed
+/// behind copro's back.
+pub struct SyncState {
+    previous: HashMap<PathBuf, Snapshot>,
+    current: HashMap<PathBuf, Snapshot>,
+}
+
+impl SyncState {
+    /// The state written by the last run, or an empty one for a first sync
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let previous = match fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content).map_err(io::Error::other)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { previous, current: HashMap::new() })
+    }
+
+    /// Whether the last sync wrote `destination` from `source` as it is now
+    pub fn unchanged(&self, source: &Path, destination: &Path, meta: &fs::Metadata) -> bool {
+        self.previous.get(&absolute(destination)) == Some(&Snapshot::of(source, meta))
+    }
+
+    /// `destination` now holds `source` as described by `meta`
+    pub fn record(&mut self, source: &Path, destination: &Path, meta: &fs::Metadata) {
+        // The state is JSON, which only holds Unicode paths
+        if source.to_str().is_some() && destination.to_str().is_some() {
+            self.current.insert(absolute(destination), Snapshot::of(source, meta));
+        }
+    }
+
+    /// `destination` turned out not to match after all
+    pub fn forget(&mut self, destination: &Path) {
+        let destination = absolute(destination);
+        self.previous.remove(&destination);
+        self.current.remove(&destination);
+    }
+
+    /// Write the state for the next run. A run that stopped part way keeps
+    /// the previous entries of the files it did not reach.
+    pub fn save(mut self, path: &Path, completed: bool) -> io::Result<()> {
+        let mut entries = if completed { HashMap::new() } else { self.previous };
+        entries.extend(self.current.drain());
+        let content = serde_json::to_vec(&entries).map_err(io::Error::other)?;
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, content)?;
+        fs::rename(&temp, path)
+    }
+}
+
+/// Paths are kept absolute, however the run named them
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,118 @@
+/// Progress shown on the taskbar button of the console window, so it stays
+/// visible while the terminal is minimized (ITaskbarList3 on Windows). Other
+/// platforms have no such hook for a console program; terminals there show the
+/// OSC 9;4 sequence instead. Not `Send`: create it on the thread that updates it.
+#[cfg(windows)]
+pub struct Taskbar {
+    list: *mut *const native::Vtbl,
+    window: windows_sys::Win32::Foundation::HWND,
+    com_initialized: bool,
+}
+
+#[cfg(windows)]
+mod native {
+    use std::ffi::c_void;
+    use windows_sys::{
+        core::{GUID, HRESULT},
+        Win32::{Foundation::HWND, UI::Shell::TBPFLAG},
+    };
+
+    pub const IID_ITASKBARLIST3: GUID = GUID::from_u128(0xea1afb91_9e28_4b86_90e9_9e9f8a5eefaf);
+
+    /// Method table of ITaskbarList3, up to the methods used here (the others
+    /// only hold their place)
+    #[repr(C)]
+    pub struct Vtbl {
+        _query_interface: usize,
+        _add_ref: usize,
+        pub release: unsafe extern "system" fn(*mut c_void) -> u32,
+        pub hr_init: unsafe extern "system" fn(*mut c_void) -> HRESULT,
+        _add_tab: usize,
+        _delete_tab: usize,
+        _activate_tab: usize,
+        _set_active_alt: usize,
+        _mark_fullscreen_window: usize,
+        pub set_progress_value: unsafe extern "system" fn(*mut c_void, HWND, u64, u64) -> HRESULT,
+        pub set_progress_state: unsafe extern "system" fn(*mut c_void, HWND, TBPFLAG) -> HRESULT,
+    }
+}
+
+#[cfg(windows)]
+impl Taskbar {
+    /// The console window's taskbar button, when there is one
+    pub fn attach() -> Option<Self> {
+        use std::ptr::{null, null_mut};
+        use windows_sys::Win32::{
+            System::{
+                Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED},
+                Console::GetConsoleWindow,
+            },
+            UI::Shell::TaskbarList,
+        };
+
+        // SAFETY: plain COM calls; the interface pointer is checked before use
+        // and released in Drop
+        unsafe {
+            let window = GetConsoleWindow();
+            if window.is_null() {
+                return None;
+            }
+            let com_initialized = CoInitializeEx(null(), COINIT_APARTMENTTHREADED as u32) >= 0;
+            let mut list = null_mut();
+            let created = CoCreateInstance(&TaskbarList, null_mut(), CLSCTX_INPROC_SERVER, &native::IID_ITASKBARLIST3, &mut list);
+            let list = list as *mut *const native::Vtbl;
+            if created < 0 || list.is_null() || ((**list).hr_init)(list.cast()) < 0 {
+                if !list.is_null() {
+                    ((**list).release)(list.cast());
+                }
+                if com_initialized {
+                    CoUninitialize();
+                }
+                return None;
+            }
+            Some(Self { list, window, com_initialized })
+        }
+    }
+
+    /// Show `percent`, in red once files have failed
+    pub fn set(&self, percent: u8, failed: bool) {
+        use windows_sys::Win32::UI::Shell::{TBPF_ERROR, TBPF_NORMAL};
+
+        let state = if failed { TBPF_ERROR } else { TBPF_NORMAL };
+        // SAFETY: `list` is a live ITaskbarList3 until Drop
+        unsafe {
+            ((**self.list).set_progress_state)(self.list.cast(), self.window, state);
+            ((**self.list).set_progress_value)(self.list.cast(), self.window, percent as u64, 100);
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Taskbar {
+    fn drop(&mut self) {
+        use windows_sys::Win32::{System::Com::CoUninitialize, UI::Shell::TBPF_NOPROGRESS};
+
+        // SAFETY: releases the reference taken in attach
+        unsafe {
+            ((**self.list).set_progress_state)(self.list.cast(), self.window, TBPF_NOPROGRESS);
+            ((**self.list).release)(self.list.cast());
+            if self.com_initialized {
+                CoUninitialize();
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub enum Taskbar {}
+
+#[cfg(not(windows))]
+impl Taskbar {
+    pub fn attach() -> Option<Self> {
+        None
+    }
+
+    pub fn set(&self, _percent: u8, _failed: bool) {
+        match *self {}
+    }
+}
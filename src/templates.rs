@@ -0,0 +1,36 @@
+use crate::state::config_dir;
+use serde::Deserialize;
+use std::{fs, io, path::PathBuf};
+
+/// User formats for the lines copro prints, read from templates.json in the
+/// config directory. Each replaces the built-in line, emoji and colors
+/// included; `{name}` placeholders are filled in, unknown ones left as they are.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Templates {
+    /// The line after a finished run: {files}, {failed}, {size}, {seconds}, {rate}
+    pub completed: Option<String>,
+    /// The lines after a stopped run: {reason}, {files}, {total}, {size}, {seconds}, {rate}
+    pub interrupted: Option<String>,
+    /// The line for each copied file with -v: {file}, {destination}, {size}, {seconds}, {rate}
+    pub file: Option<String>,
+}
+
+/// Where the templates are looked for
+pub fn templates_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("templates.json"))
+}
+
+impl Templates {
+    /// The user's templates; none when there is no file
+    pub fn load() -> io::Result<Self> {
+        let Some(file) = templates_file() else {
+            return Ok(Self::default());
+        };
+        match fs::read(&file) {
+            Ok(content) => serde_json::from_slice(&content).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+}
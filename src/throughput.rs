@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+/// How quickly the estimate follows a change of speed: after this long at a
+/// new speed it has covered about two thirds of the difference
+const TIME_CONSTANT: f64 = 5.0;
+
+/// Samples closer together than this are merged, so a fast redraw does not
+/// see the jumps of a file landing all at once
+const MIN_SAMPLE: Duration = Duration::from_millis(500);
+
+/// A pause in progress shorter than this keeps the last estimate instead of
+/// dragging it towards zero (a large file being opened, a directory created)
+const STALL_HOLD: Duration = Duration::from_secs(10);
+
+/// Copy speed smoothed with an exponentially weighted moving average, so the
+/// rate and time left do not swing between large and small files
+pub struct Throughput {
+    /// Bytes done at the last sample, and when
+    last: (Instant, u64),
+    /// When the byte count last moved
+    moved: Instant,
+    rate: Option<f64>,
+}
+
+impl Default for Throughput {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self { last: (now, 0), moved: now, rate: None }
+    }
+}
+
+impl Throughput {
+    /// Take `bytes` done so far into account and return the smoothed rate in
+    /// bytes per second, once there is one
+    pub fn sample(&mut self, bytes: u64) -> Option<f64> {
+        let now = Instant::now();
+        let (at, done) = self.last;
+        let since = now.duration_since(at);
+        if since < MIN_SAMPLE {
+            return self.rate;
+        }
+        if bytes <= done && now.duration_since(self.moved) < STALL_HOLD {
+            return self.rate;
+        }
+        if bytes > done {
+            self.moved = now;
+        }
+        let instant = bytes.saturating_sub(done) as f64 / since.as_secs_f64();
+        let weight = 1.0 - (-since.as_secs_f64() / TIME_CONSTANT).exp();
+        self.rate = Some(match self.rate {
+            Some(rate) => rate + weight * (instant - rate),
+            None => instant,
+        });
+        self.last = (now, bytes);
+        self.rate
+    }
+
+    /// Time left for `remaining` bytes at the current estimate; none while
+    /// the estimate is too low to mean anything
+    pub fn eta(&self, remaining: u64) -> Option<Duration> {
+        let rate = self.rate.filter(|rate| *rate >= 1.0)?;
+        Some(Duration::from_secs_f64(remaining as f64 / rate).min(Duration::from_secs(100 * 3600)))
+    }
+}
+
+/// Remaining time as `1h05m`, `4m20s` or `12s`
+pub fn format_eta(left: Duration) -> String {
+    let seconds = left.as_secs_f64().round() as u64;
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m{:02}s", m, s),
+        (h, m, _) => format!("{}h{:02}m", h, m),
+    }
+}
@@ -0,0 +1,226 @@
+use crate::state::state_dir;
+use anyhow::{Context, Result};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+/// With --undo-copies, overwritten files that cannot be hard-linked are only
+/// copied up to this size, and up to this much in all for a run
+const MAX_BACKUP_COPY: u64 = 256 * 1024 * 1024;
+const MAX_BACKUP_TOTAL: u64 = 1024 * 1024 * 1024;
+
+/// Arguments of `copro undo`
+#[derive(clap::Args, Clone)]
+pub struct UndoArgs {
+    /// skip the confirmation prompt
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// One change made by a run, as a line of the undo log
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Entry {
+    /// A directory the run created
+    Dir(PathBuf),
+    /// A file that did not exist before the run
+    Created(PathBuf),
+    /// A file the run replaced, and where its previous content was kept
+    Overwritten { path: PathBuf, backup: Option<PathBuf> },
+}
+
+/// Where the undo log of the most recent run lives
+fn undo_dir() -> io::Result<PathBuf> {
+    state_dir()
+        .map(|dir| dir.join("undo"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory (HOME is not set)"))
+}
+
+/// The undo log being written by the current run. Entries are appended as
+/// they happen, so an interrupted run can be undone too.
+pub struct UndoLog {
+    dir: PathBuf,
+    file: fs::File,
+    backups: usize,
+    /// Bytes of overwritten files that may still be copied into the backups
+    copy_budget: u64,
+}
+
+impl UndoLog {
+    /// Start the log of a new run, discarding the previous run's log and
+    /// backups. Overwritten files are kept by hard link; with `copies`, those
+    /// that cannot be linked are copied, within the limits above.
+    pub fn start(copies: bool) -> io::Result<Self> {
+        let dir = undo_dir()?;
+        match fs::remove_dir_all(&dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        fs::create_dir_all(dir.join("backups"))?;
+        let file = fs::File::create(dir.join("log.jsonl"))?;
+        let copy_budget = if copies { MAX_BACKUP_TOTAL } else { 0 };
+        Ok(Self { dir, file, backups: 0, copy_budget })
+    }
+
+    /// Record that `path` is about to be written: the directories that will be
+    /// created for it, and whether it is new or replaces a file, keeping the
+    /// old content when feasible. `in_place` copies overwrite the existing file
+    /// rather than renaming over it, so a hard link would not preserve it.
+    pub fn before_write(&mut self, path: &Path, in_place: bool) -> io::Result<()> {
+        let path = std::path::absolute(path)?;
+        let mut missing: Vec<&Path> = path.ancestors().skip(1).take_while(|dir| !dir.exists()).collect();
+        missing.reverse();
+        for dir in missing {
+            self.append(&Entry::Dir(dir.to_path_buf()))?;
+        }
+
+        let entry = match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.is_dir() => return Ok(()),
+            Ok(meta) => Entry::Overwritten { backup: self.back_up(&path, meta.len(), in_place), path },
+            Err(_) => Entry::Created(path),
+        };
+        self.append(&entry)
+    }
+
+    /// Keep the current content of `path` in the backups folder, by hard link
+    /// when the copy replaces the file (only possible on the filesystem of the
+    /// state directory), otherwise by copying it if the budget allows
+    fn back_up(&mut self, path: &Path, size: u64, in_place: bool) -> Option<PathBuf> {
+        self.backups += 1;
+        let backup = self.dir.join("backups").join(self.backups.to_string());
+        if !in_place && fs::hard_link(path, &backup).is_ok() {
+            return Some(backup);
+        }
+        if size > MAX_BACKUP_COPY || size > self.copy_budget || fs::copy(path, &backup).is_err() {
+            return None;
+        }
+        self.copy_budget -= size;
+        Some(backup)
+    }
+
+    /// Flush the log to disk, so an interrupted run can still be undone
+    /// after a crash
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    fn append(&mut self, entry: &Entry) -> io::Result<()> {
+        let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        writeln!(self.file, "{}", line)
+    }
+}
+
+/// Drop the previous run's log, so `copro undo` never reaches past a run
+/// that was not recorded
+pub fn discard() {
+    if let Ok(dir) = undo_dir() {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+/// Roll back the most recent run: remove the files and directories it
+/// created and put back the files it overwrote
+pub fn run(args: &UndoArgs) -> Result<()> {
+    let dir = undo_dir()?;
+    let entries = match fs::File::open(dir.join("log.jsonl")) {
+        Ok(file) => io::BufReader::new(file)
+            .lines()
+            .map(|line| Ok(serde_json::from_str::<Entry>(&line?)?))
+            .collect::<Result<Vec<_>>>()
+            .context("could not read the undo log")?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e).context("could not read the undo log"),
+    };
+    if entries.is_empty() {
+        println!("{}{}", icon!("💡 "), tr!("Nothing to undo"));
+        return Ok(());
+    }
+
+    let created = entries.iter().filter(|e| matches!(e, Entry::Created(_))).count();
+    let overwritten = entries.iter().filter(|e| matches!(e, Entry::Overwritten { .. })).count();
+    println!("{}{} {}", icon!("⏪ "),
+        style(tr!("UNDO")).cyan().bold(),
+        tr!("the last run: remove {created} created files, restore {overwritten} overwritten files",
+            created = style(created).yellow(),
+            overwritten = style(overwritten).yellow()
+        )
+    );
+
+    #[cfg(feature = "prompts")]
+    if !args.yes {
+        if !console::user_attended() {
+            eprintln!("{} {}",
+                style(icon!("❌ ", "ERROR:")).red().bold(),
+                tr!("Confirmation required but not running interactively (pass --yes to skip it)")
+            );
+            return Ok(());
+        }
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(tr!("Undo the last run?"))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("{} {}", style(icon!("🚫 ", "CANCELLED:")).yellow().bold(), tr!("Nothing was changed"));
+            return Ok(());
+        }
+    }
+    #[cfg(not(feature = "prompts"))]
+    let _ = args.yes;
+
+    // Newest first, so files go before the directories that hold them
+    let mut problems = 0;
+    for entry in entries.iter().rev() {
+        let result = match entry {
+            Entry::Dir(path) => {
+                // Only if nothing else has been put there since
+                let _ = fs::remove_dir(path);
+                Ok(())
+            }
+            Entry::Created(path) => match fs::remove_file(path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                result => result.map_err(|e| (path, e.to_string())),
+            },
+            Entry::Overwritten { path, backup: Some(backup) } => restore(backup, path).map_err(|e| (path, e.to_string())),
+            Entry::Overwritten { path, backup: None } => Err((path, String::from(tr!("no backup was kept (not linkable, and not copied without --undo-copies or when too large)")))),
+        };
+        if let Err((path, reason)) = result {
+            problems += 1;
+            eprintln!("{} {} ({})",
+                style(icon!("⚠️ ", "Not undone:")).yellow().bold(),
+                style(path.display()).white(),
+                reason
+            );
+        }
+    }
+
+    if problems == 0 {
+        fs::remove_dir_all(&dir).context("could not remove the undo log")?;
+        println!("{} {}", style(icon!("✅ ", "DONE:")).green().bold(), tr!("The last run was undone"));
+    } else {
+        // Keep the log and any backups that could not be put back
+        println!("{} {}",
+            style(icon!("⚠️ ", "PARTIAL:")).yellow().bold(),
+            tr!("The last run was undone except for {count} files (backups are kept in {path})",
+                count = style(problems).yellow(),
+                path = style(dir.join("backups").display()).white()
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Put `backup` back at `path`, copying when they are on different filesystems
+fn restore(backup: &Path, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(backup, path).is_err() {
+        fs::copy(backup, path)?;
+        fs::remove_file(backup)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use crate::{hashcache::HashCache, merge::Sources, walk};
+use anyhow::{bail, Context, Result};
+use console::style;
+use std::{
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+/// Arguments of `copro verify`
+#[derive(clap::Args, Clone)]
+pub struct VerifyArgs {
+    /// source the destination was copied from
+    source: PathBuf,
+
+    /// destination to compare with it, as given to copro cp
+    destination: PathBuf,
+
+    /// number of files hashed in parallel (default: chosen from the CPU count and the drives, as
+    /// for --verify-threads)
+    #[arg(long, value_name = "N")]
+    threads: Option<usize>,
+}
+
+/// A finished copy to re-read: `range` (offset, length) of `source` should
+/// now be exactly what `destination` holds
+pub struct Copied {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub range: (u64, u64),
+    /// Hash of the source data taken while it was copied, which saves reading it again
+    pub source_hash: Option<blake3::Hash>,
+}
+
+/// How a copy differs from its source
+pub enum Mismatch {
+    Size(u64, u64),
+    Content,
+    Unreadable(String),
+}
+
+impl Mismatch {
+    pub fn describe(&self) -> String {
+        match self {
+            Mismatch::Size(expected, actual) => format!("size differs: {} → {} bytes", expected, actual),
+            Mismatch::Content => String::from("content differs from the source (hash mismatch)"),
+            Mismatch::Unreadable(e) => format!("could not be read back: {}", e),
+        }
+    }
+}
+
+impl Copied {
+    /// Bytes read to verify this copy (the destination, and the source unless
+    /// it was hashed in flight)
+    pub fn cost(&self) -> u64 {
+        match self.source_hash {
+            Some(_) => self.range.1,
+            None => self.range.1 * 2,
+        }
+    }
+
+    /// Hash the destination and compare it with the source range; `counter`
+    /// grows by every byte read. A whole source file unchanged since an
+    /// earlier run takes its hash from `cache`; the copy is always read.
+    pub fn check(&self, counter: &AtomicU64, interrupted: &AtomicBool, cache: Option<&HashCache>) -> io::Result<Option<Mismatch>> {
+        let (offset, length) = self.range;
+        let actual = match fs::metadata(&self.destination) {
+            Ok(meta) => meta.len(),
+            Err(e) => return Ok(Some(Mismatch::Unreadable(e.to_string()))),
+        };
+        if actual != length {
+            return Ok(Some(Mismatch::Size(length, actual)));
+        }
+        let whole = offset == 0 && fs::metadata(&self.source).is_ok_and(|meta| meta.len() == length);
+        let cache = cache.filter(|_| whole);
+        let expected = match (self.source_hash, cache.and_then(|cache| cache.get(&self.source))) {
+            (Some(hash), _) | (None, Some(hash)) => hash,
+            (None, None) => hash(&self.source, offset, length, counter, interrupted)?,
+        };
+        match hash(&self.destination, 0, length, counter, interrupted) {
+            Ok(hash) if hash == expected => {
+                if let Some(cache) = cache {
+                    cache.insert(&self.source, expected);
+                    cache.insert(&self.destination, hash);
+                }
+                Ok(None)
+            }
+            Ok(_) => Ok(Some(Mismatch::Content)),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => Err(e),
+            Err(e) => Ok(Some(Mismatch::Unreadable(e.to_string()))),
+        }
+    }
+}
+
+/// BLAKE3 of `length` bytes of `path` from `offset`, reporting progress and
+/// stopping early on interruption
+fn hash(path: &Path, offset: u64, length: u64, counter: &AtomicU64, interrupted: &AtomicBool) -> io::Result<blake3::Hash> {
+    let mut file = crate::noatime::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = file.take(length);
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        if interrupted.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "verification interrupted"));
+        }
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        counter.fetch_add(read as u64, Ordering::Relaxed);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Hash every file of a source and its copy in the destination, the way
+/// --verify does after a copy. Returns whether every copy matches.
+pub fn run(args: &VerifyArgs) -> Result<bool> {
+    if !args.source.exists() {
+        bail!("source path does not exist: {}", args.source.display());
+    }
+    let sources = Sources::single(args.source.clone());
+    let notes = walk::Notes::default();
+    let copies = walk::Walker::new(&args.source, None, walk::BrokenLinks::Skip, &notes)
+        .map(|file| {
+            let file = file?;
+            let destination = crate::output_path(&file.path, &sources, &args.destination, None, false);
+            Ok(Copied { range: (0, file.size()), source: file.path, destination, source_hash: None })
+        })
+        .collect::<io::Result<Vec<_>>>()
+        .with_context(|| format!("could not read {}", args.source.display()))?;
+
+    println!("{}{} {}", icon!("🔍 "),
+        style(tr!("VERIFYING")).cyan().bold(),
+        tr!("{count} files of {source} against {destination}",
+            count = style(copies.len()).yellow(),
+            source = style(args.source.display()).white(),
+            destination = style(args.destination.display()).white()
+        )
+    );
+    let (_, interrupted) = crate::setup_signal_handler(None)?;
+    let Some(mismatches) = crate::verify_copies(&copies, None, args.threads, &interrupted) else {
+        println!("{}{} {}", icon!("🛑 "),
+            style(tr!("INTERRUPTED!")).red().bold(),
+            tr!("Verification stopped before every copy was checked")
+        );
+        return Ok(false);
+    };
+    for (destination, mismatch) in &mismatches {
+        println!("{} {} ({})",
+            style(icon!("❌ ", "Mismatch:")).red().bold(),
+            style(destination.display()).white(),
+            style(mismatch.describe()).red()
+        );
+    }
+    if mismatches.is_empty() {
+        println!("{}{} {}", icon!("🔍 "),
+            style(tr!("VERIFIED!")).green().bold(),
+            tr!("{count} copies match their source", count = style(copies.len()).cyan().bold())
+        );
+    } else {
+        println!("{}{} {}", icon!("⚠️  "),
+            style(tr!("VERIFY FAILED!")).red().bold(),
+            tr!("{count} of {total} copies differ from their source",
+                count = style(mismatches.len()).red().bold(),
+                total = style(copies.len()).cyan()
+            )
+        );
+    }
+    Ok(mismatches.is_empty())
+}
@@ -0,0 +1,38 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// `path` with a version suffix: `report.pdf.~2~`
+pub fn versioned(path: &Path, version: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".~{version}~"));
+    PathBuf::from(name)
+}
+
+/// Keep the file at `path` as version 1 before it is overwritten, moving the
+/// older versions up one and dropping the one beyond `keep`. With `in_place`
+/// the file is moved aside, as the copy is about to write into it; otherwise
+/// it stays where it is until the copy replaces it, sharing its data with
+/// version 1 where the filesystem allows hard links.
+pub fn rotate(path: &Path, keep: usize, in_place: bool) -> io::Result<()> {
+    if keep == 0 || fs::symlink_metadata(path).is_err() {
+        return Ok(());
+    }
+    match fs::remove_file(versioned(path, keep)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    for version in (1..keep).rev() {
+        match fs::rename(versioned(path, version), versioned(path, version + 1)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+
+    let first = versioned(path, 1);
+    if in_place {
+        return fs::rename(path, first);
+    }
+    fs::hard_link(path, &first).or_else(|_| fs::copy(path, &first).map(|_| ()))
+}
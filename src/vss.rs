@@ -0,0 +1,149 @@
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+};
+
+/// IDs of the shadow copies this process created and has not deleted, so
+/// they can be deleted on the way out of an exit that skips Drop
+static HELD: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Volume Shadow Copies of the source volumes, deleted again when dropped
+pub struct Snapshots {
+    snapshots: Vec<Snapshot>,
+}
+
+struct Snapshot {
+    id: String,
+    /// Volume root the snapshot was taken of, e.g. `C:\`
+    volume: PathBuf,
+    /// Snapshot device, e.g. `\\?\GLOBALROOT\Device\HarddiskVolumeShadowCopy3`
+    device: PathBuf,
+}
+
+impl Snapshots {
+    /// Snapshot every volume holding one of `paths` (needs an elevated prompt)
+    pub fn create(paths: &[PathBuf]) -> io::Result<Self> {
+        if !cfg!(windows) {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "--vss is only available on Windows"));
+        }
+        let mut snapshots: Vec<Snapshot> = Vec::new();
+        for path in paths {
+            let volume = volume_of(path)?;
+            if !snapshots.iter().any(|s| s.volume == volume) {
+                snapshots.push(Snapshot::create(volume)?);
+            }
+        }
+        Ok(Self { snapshots })
+    }
+
+    /// Where `path` can be read inside its volume's snapshot
+    pub fn map(&self, path: &Path) -> PathBuf {
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        for snapshot in &self.snapshots {
+            if let Ok(rest) = absolute.strip_prefix(&snapshot.volume) {
+                return snapshot.device.join(rest);
+            }
+        }
+        path.to_path_buf()
+    }
+
+    /// `(volume, snapshot device)` pairs for display
+    pub fn volumes(&self) -> impl Iterator<Item = (&Path, &Path)> {
+        self.snapshots.iter().map(|s| (s.volume.as_path(), s.device.as_path()))
+    }
+}
+
+/// Root of the drive `path` lives on, e.g. `C:\`
+fn volume_of(path: &Path) -> io::Result<PathBuf> {
+    let absolute = std::path::absolute(path)?;
+    match absolute.components().next() {
+        Some(Component::Prefix(prefix)) => {
+            let mut volume = PathBuf::from(prefix.as_os_str());
+            volume.push(Component::RootDir);
+            Ok(volume)
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} is not on a drive that can be snapshotted", path.display()),
+        )),
+    }
+}
+
+impl Snapshot {
+    #[cfg(windows)]
+    fn create(volume: PathBuf) -> io::Result<Self> {
+        let script = format!(
+            "$r = Invoke-CimMethod -ClassName Win32_ShadowCopy -MethodName Create \
+             -Arguments @{{Volume='{}'; Context='ClientAccessible'}}; \
+             if ($r.ReturnValue -ne 0) {{ [Console]::Error.WriteLine(\"Win32_ShadowCopy.Create returned $($r.ReturnValue)\"); exit 1 }}; \
+             $r.ShadowID; (Get-CimInstance Win32_ShadowCopy | Where-Object ID -eq $r.ShadowID).DeviceObject",
+            volume.display()
+        );
+        let output = powershell(&script)?;
+        let mut lines = output.lines().map(str::trim).filter(|l| !l.is_empty());
+        match (lines.next(), lines.next()) {
+            (Some(id), Some(device)) => {
+                if let Ok(mut held) = HELD.lock() {
+                    held.push(id.to_string());
+                }
+                Ok(Self {
+                    id: id.to_string(),
+                    volume,
+                    device: PathBuf::from(device),
+                })
+            }
+            _ => Err(io::Error::other("could not read the shadow copy device")),
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn create(_volume: PathBuf) -> io::Result<Self> {
+        unreachable!("shadow copies are only created on Windows")
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        if let Ok(mut held) = HELD.lock() {
+            held.retain(|id| *id != self.id);
+        }
+        delete(&self.id);
+    }
+}
+
+/// Delete every shadow copy this process still holds, before a
+/// `process::exit`, which drops nothing
+pub fn release_all() {
+    if let Ok(mut held) = HELD.lock() {
+        for id in held.drain(..) {
+            delete(&id);
+        }
+    }
+}
+
+fn delete(id: &str) {
+    #[cfg(windows)]
+    let _ = powershell(&format!(
+        "Get-CimInstance Win32_ShadowCopy | Where-Object ID -eq '{}' | Remove-CimInstance",
+        id
+    ));
+    #[cfg(not(windows))]
+    let _ = id;
+}
+
+/// Run a PowerShell snippet and return its standard output
+#[cfg(windows)]
+fn powershell(script: &str) -> io::Result<String> {
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(format!(
+            "could not create a shadow copy (copro must run as Administrator): {}",
+            message
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
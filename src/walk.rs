@@ -0,0 +1,252 @@
+use crate::{resolve_path, storage, ScanProgress};
+use clap::ValueEnum;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Files discovered ahead of the copy; bounds memory while keeping the copier fed
+const QUEUE_DEPTH: usize = 4096;
+
+/// Per-directory state: the directories above it; per-entry state: the file's
+/// metadata, read on the walker's worker threads
+type Client = (Vec<PathBuf>, Option<fs::Metadata>);
+
+/// A file found by the walk with the metadata read for it there, carried
+/// along so nothing later has to stat it again (a round trip each on NFS
+/// or SMB)
+#[derive(Clone)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    /// None for a dangling symlink, which has no target to describe
+    pub metadata: Option<fs::Metadata>,
+}
+
+impl FileEntry {
+    pub fn size(&self) -> u64 {
+        self.metadata.as_ref().map_or(0, fs::Metadata::len)
+    }
+}
+
+/// What to do with symlinks whose target does not exist
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BrokenLinks {
+    /// leave them out and list them in the summary
+    Skip,
+    /// create the same dangling link at the destination
+    Recreate,
+    /// count each one as a failed file
+    Fail,
+}
+
+/// Something the walk deliberately left out
+pub enum Note {
+    /// A symlinked directory leading back to `ancestor`, which was not followed
+    Loop { ancestor: PathBuf },
+    /// A symlink to `target`, which does not exist
+    Broken { target: PathBuf },
+    /// A directory (or entry) that could not be read, e.g. permission denied
+    Unreadable { error: String },
+}
+
+/// What the walk skipped, shared between walker threads and reported once at
+/// the end (each path once, however many times the source is walked)
+#[derive(Clone, Default)]
+pub struct Notes(Arc<Mutex<BTreeMap<PathBuf, Note>>>);
+
+impl Notes {
+    fn add(&self, path: PathBuf, note: Note) {
+        if let Ok(mut notes) = self.0.lock() {
+            notes.entry(path).or_insert(note);
+        }
+    }
+
+    pub fn take(&self) -> BTreeMap<PathBuf, Note> {
+        self.0.lock().map(|mut notes| std::mem::take(&mut *notes)).unwrap_or_default()
+    }
+}
+
+/// Walk of the files under a source. Directories are read and files stat'ed in
+/// parallel (jwalk on rayon), which matters on network filesystems where
+/// readdir and stat latency dominate; files still come out in walk order.
+/// A source on a spinning disk is walked on one thread.
+pub struct Walker {
+    entries: jwalk::DirEntryIter<Client>,
+    broken_links: BrokenLinks,
+    notes: Notes,
+}
+
+impl Walker {
+    /// Walk `root` (a file yields just itself), never descending into `skip_dir`.
+    /// Dangling symlinks are yielded as files of size 0 unless `broken_links` skips them.
+    pub fn new(root: &Path, skip_dir: Option<&Path>, broken_links: BrokenLinks, notes: &Notes) -> Self {
+        let skip_dir = skip_dir.map(Path::to_path_buf);
+        let loops = notes.clone();
+        // Parallel readdir and stat only make a spinning disk seek
+        let parallelism = match storage::workers(&[root]) {
+            1 => jwalk::Parallelism::Serial,
+            _ => jwalk::Parallelism::RayonDefaultPool { busy_timeout: Duration::from_secs(1) },
+        };
+        let entries = jwalk::WalkDirGeneric::<Client>::new(root)
+            .parallelism(parallelism)
+            .follow_links(true)
+            .skip_hidden(false)
+            .process_read_dir(move |depth, dir, ancestors, children| {
+                // Children inherit a copy of this list once we return
+                if depth.is_some() {
+                    ancestors.push(dir.to_path_buf());
+                }
+
+                // A symlinked directory that is one of its own ancestors (`a -> .`)
+                // would be walked forever; compare device and inode, not paths
+                for entry in children.iter_mut().flatten() {
+                    if !(entry.file_type.is_dir() && entry.path_is_symlink()) {
+                        continue;
+                    }
+                    let path = entry.path();
+                    if let Some(ancestor) = ancestors.iter().find(|a| same_dir(a, &path)) {
+                        loops.add(path, Note::Loop { ancestor: ancestor.clone() });
+                        entry.read_children_path = None;
+                    }
+                }
+
+                // Never descend into our own output when it lives inside the source
+                if let Some(skip) = &skip_dir {
+                    children.retain(|child| match child {
+                        Ok(entry) => !(entry.file_type.is_dir() && resolve_path(&entry.path()) == *skip),
+                        Err(_) => true,
+                    });
+                }
+                for entry in children.iter_mut().flatten() {
+                    if entry.file_type.is_file() {
+                        entry.client_state = entry.metadata().ok();
+                    }
+                }
+            })
+            .into_iter();
+        Self { entries, broken_links, notes: notes.clone() }
+    }
+}
+
+impl Iterator for Walker {
+    type Item = io::Result<FileEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut entry = match self.entries.next()? {
+                Ok(entry) => entry,
+                // Following a dangling symlink fails; the link itself is still there
+                Err(e) if e.io_error().is_some_and(|e| e.kind() == io::ErrorKind::NotFound) => {
+                    let Some(path) = e.path().filter(|p| is_dangling(p)) else {
+                        continue;
+                    };
+                    if self.broken_links != BrokenLinks::Skip {
+                        return Some(Ok(FileEntry { path: path.to_path_buf(), metadata: None }));
+                    }
+                    let target = fs::read_link(path).unwrap_or_default();
+                    self.notes.add(path.to_path_buf(), Note::Broken { target });
+                    continue;
+                }
+                // jwalk's own check, for links naming an ancestor by its path
+                Err(e) if e.loop_ancestor().is_some() => {
+                    if let (Some(path), Some(ancestor)) = (e.path(), e.loop_ancestor()) {
+                        self.notes.add(path.to_path_buf(), Note::Loop { ancestor: ancestor.to_path_buf() });
+                    }
+                    continue;
+                }
+                // Keep walking the rest of the tree; the summary lists what was missed
+                Err(e) => match e.path() {
+                    Some(path) => {
+                        let path = path.to_path_buf();
+                        self.notes.add(path, Note::Unreadable { error: reason(e) });
+                        continue;
+                    }
+                    None => return Some(Err(e.into())),
+                },
+            };
+            if let Some(e) = entry.read_children_error.take() {
+                self.notes.add(entry.path(), Note::Unreadable { error: reason(e) });
+                continue;
+            }
+            if entry.file_type.is_file() {
+                // The root is not passed through process_read_dir
+                let metadata = entry.client_state.take().or_else(|| entry.metadata().ok());
+                return Some(Ok(FileEntry { path: entry.path(), metadata }));
+            }
+        }
+    }
+}
+
+/// Every file of `files` and their total size; `found` sees each file's size
+/// as it is counted. The entries are kept so the copy does not walk (and
+/// stat) the tree a second time.
+pub fn tally(files: impl Iterator<Item = io::Result<FileEntry>>, found: impl Fn(u64)) -> io::Result<(Vec<FileEntry>, u64)> {
+    let mut listing = Vec::new();
+    let mut bytes = 0;
+    for file in files {
+        let file = file?;
+        found(file.size());
+        bytes += file.size();
+        listing.push(file);
+    }
+    Ok((listing, bytes))
+}
+
+/// Walk `root` on a background thread, handing files over through a bounded
+/// queue so copying starts right away, while `scan` keeps the totals current
+pub fn spawn(
+    root: &Path,
+    skip_dir: Option<&Path>,
+    broken_links: BrokenLinks,
+    notes: &Notes,
+    scan: ScanProgress,
+) -> mpsc::Receiver<io::Result<FileEntry>> {
+    let (tx, rx) = mpsc::sync_channel(QUEUE_DEPTH);
+    let walker = Walker::new(root, skip_dir, broken_links, notes);
+    thread::spawn(move || {
+        for file in walker {
+            if let Ok(file) = &file {
+                scan.found(file.size());
+            }
+            // The copy loop hung up (stopped early); nothing left to do
+            if tx.send(file).is_err() {
+                break;
+            }
+        }
+        scan.finished();
+    });
+    rx
+}
+
+/// The OS error behind a walk error, without jwalk's path prefix
+fn reason(error: jwalk::Error) -> String {
+    match error.io_error() {
+        Some(e) => e.to_string(),
+        None => error.to_string(),
+    }
+}
+
+/// Whether two paths are the same directory on disk
+fn same_dir(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (fs::metadata(a), fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+    }
+}
+
+/// Whether `path` is a symlink whose target does not exist
+pub fn is_dangling(path: &Path) -> bool {
+    !path.exists() && fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+}
@@ -0,0 +1,193 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often the watchdog looks at the copy in progress
+const TICK: Duration = Duration::from_millis(250);
+
+/// When a copy counts as stuck, and what to do about it
+pub struct Limits {
+    /// No data moving for this long is a stall
+    pub stall: Duration,
+    /// Give up on a stalled file instead of waiting for it
+    pub abandon_stalled: bool,
+    /// Give up on any file whose copy takes longer than this
+    pub file_timeout: Option<Duration>,
+}
+
+/// When the data of the file being copied last moved, for the progress line
+pub struct Stall {
+    moved: Mutex<Option<Instant>>,
+    after: Duration,
+}
+
+impl Stall {
+    /// How long the current file has been stalled, once that exceeds the limit
+    pub fn stalled_for(&self) -> Option<Duration> {
+        let moved = (*self.moved.lock().ok()?)?;
+        Some(moved.elapsed()).filter(|stalled| *stalled >= self.after)
+    }
+}
+
+/// The copy being watched
+struct Current {
+    /// Files the copy writes; their growth counts as progress for copies
+    /// that do not report to the byte counter
+    targets: Vec<PathBuf>,
+    seen: (u64, u64),
+    started: Instant,
+}
+
+struct Shared {
+    current: Mutex<Option<Current>>,
+    stall: Arc<Stall>,
+    abandoned: Mutex<Option<String>>,
+    cancel: AtomicBool,
+    stop: AtomicBool,
+}
+
+/// Watches the file being copied, so a copy that hangs (a dead network mount,
+/// a failing disk) shows up in the progress line instead of freezing it, and
+/// can be abandoned, as can a copy that exceeds its time budget. Data counts as moving while the run's byte counter or a
+/// file being written grows.
+pub struct Watchdog {
+    shared: Arc<Shared>,
+    interrupted: Arc<AtomicBool>,
+    abandons: bool,
+    thread: thread::Thread,
+}
+
+impl Watchdog {
+    /// Start watching; `bytes` is the run's byte counter
+    pub fn start(limits: Limits, bytes: Arc<AtomicU64>, interrupted: Arc<AtomicBool>) -> Self {
+        let shared = Arc::new(Shared {
+            current: Mutex::new(None),
+            stall: Arc::new(Stall { moved: Mutex::new(None), after: limits.stall }),
+            abandoned: Mutex::new(None),
+            cancel: AtomicBool::new(false),
+            stop: AtomicBool::new(false),
+        });
+
+        let thread = {
+            let shared = Arc::clone(&shared);
+            let interrupted = Arc::clone(&interrupted);
+            thread::spawn(move || {
+                while !shared.stop.load(Ordering::Relaxed) {
+                    if interrupted.load(Ordering::Relaxed) {
+                        shared.cancel.store(true, Ordering::Relaxed);
+                    }
+                    // Looked at outside the lock: on a hung mount this is what hangs
+                    let targets = shared.current.lock().ok().and_then(|c| c.as_ref().map(|c| c.targets.clone()));
+                    if let Some(targets) = targets {
+                        let seen = (bytes.load(Ordering::Relaxed), written(&targets));
+                        if let Ok(mut current) = shared.current.lock() {
+                            match current.as_mut() {
+                                Some(current) if current.targets == targets && current.seen != seen => {
+                                    current.seen = seen;
+                                    if let Ok(mut moved) = shared.stall.moved.lock() {
+                                        *moved = Some(Instant::now());
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    if let Some(stalled) = shared.stall.stalled_for().filter(|_| limits.abandon_stalled) {
+                        shared.abandon(format!("stalled: no data moved for {}s", stalled.as_secs()));
+                    }
+                    if let Some(timeout) = limits.file_timeout {
+                        let started = shared.current.lock().ok().and_then(|c| c.as_ref().map(|c| c.started));
+                        if started.is_some_and(|started| started.elapsed() > timeout) {
+                            shared.abandon(format!("timed out after {}s", timeout.as_secs()));
+                        }
+                    }
+                    thread::park_timeout(TICK);
+                }
+            })
+            .thread()
+            .clone()
+        };
+
+        let abandons = limits.abandon_stalled || limits.file_timeout.is_some();
+        Self { shared, interrupted, abandons, thread }
+    }
+
+    /// Start watching a copy that writes `targets`
+    pub fn begin(&self, targets: Vec<PathBuf>) {
+        if let Ok(mut abandoned) = self.shared.abandoned.lock() {
+            *abandoned = None;
+        }
+        self.shared.cancel.store(self.interrupted.load(Ordering::Relaxed), Ordering::Relaxed);
+        if let Ok(mut moved) = self.shared.stall.moved.lock() {
+            *moved = Some(Instant::now());
+        }
+        if let Ok(mut current) = self.shared.current.lock() {
+            *current = Some(Current { targets, seen: (0, 0), started: Instant::now() });
+        }
+    }
+
+    /// The copy is over, whatever its outcome
+    pub fn end(&self) {
+        if let Ok(mut current) = self.shared.current.lock() {
+            *current = None;
+        }
+        if let Ok(mut moved) = self.shared.stall.moved.lock() {
+            *moved = None;
+        }
+    }
+
+    /// Whether copies have to be stoppable part way, which rules out handing
+    /// them whole to the operating system
+    pub fn abandons(&self) -> bool {
+        self.abandons
+    }
+
+    /// The flag copies check between blocks: set by Ctrl+C and, when files
+    /// may be abandoned, for the file being given up on
+    pub fn cancel_flag(&self) -> &AtomicBool {
+        if self.abandons { &self.shared.cancel } else { &self.interrupted }
+    }
+
+    /// Why the current file was given up on, if it was
+    pub fn abandoned(&self) -> Option<String> {
+        self.shared.abandoned.lock().ok()?.clone()
+    }
+
+    /// Stall state for the progress line
+    pub fn stall(&self) -> Arc<Stall> {
+        Arc::clone(&self.shared.stall)
+    }
+}
+
+impl Shared {
+    /// Stop the current copy at its next block, remembering why
+    fn abandon(&self, reason: String) {
+        let Ok(mut abandoned) = self.abandoned.lock() else {
+            return;
+        };
+        if abandoned.is_none() {
+            *abandoned = Some(reason);
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        // Not joined: a thread stuck on a dead mount would hold up the exit
+        self.shared.stop.store(true, Ordering::Relaxed);
+        self.thread.unpark();
+    }
+}
+
+/// Bytes written so far to the files of a copy
+fn written(targets: &[PathBuf]) -> u64 {
+    targets.iter().filter_map(|target| fs::metadata(target).ok()).map(|meta| meta.len()).sum()
+}
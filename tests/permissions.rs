@@ -0,0 +1,51 @@
+#![cfg(unix)]
+
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// A fresh directory under the system temp dir, removed first if a
+/// previous run left it
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("copro-test-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn copro(dir: &PathBuf, args: &[&str]) {
+    let status = Command::new(env!("CARGO_BIN_EXE_copro"))
+        .args(args)
+        .arg("-y")
+        .current_dir(dir)
+        .env("XDG_STATE_HOME", dir.join("state"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success(), "copro {args:?} failed: {status}");
+}
+
+fn mode(path: PathBuf) -> u32 {
+    fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[test]
+fn verify_keeps_executable_bits() {
+    let dir = scratch("verify");
+    fs::create_dir(dir.join("src")).unwrap();
+    fs::write(dir.join("src/run.sh"), "#!/bin/sh\necho hi\n").unwrap();
+    fs::set_permissions(dir.join("src/run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+
+    copro(&dir, &["--verify", "src", "dst"]);
+    assert_eq!(mode(dir.join("dst/run.sh")), 0o755);
+
+    copro(&dir, &["--verify", "src/run.sh", "single.sh"]);
+    assert_eq!(mode(dir.join("single.sh")), 0o755);
+
+    fs::remove_dir_all(&dir).unwrap();
+}